@@ -0,0 +1,291 @@
+//! The members of ZIP and TAR archives, found where they lie in the input so that
+//! each can be shown on its own without unpacking anything.
+
+use std::cmp;
+use std::io::{self, Read};
+
+use error::Error;
+use source::Source;
+
+/// One file in an archive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Member {
+    pub name: String,
+    /// Where its bytes start in the archive, or in what the archive decompresses to.
+    pub offset: u64,
+    /// Bytes it takes in the archive.
+    pub len: u64,
+    /// Bytes once inflated, `len` unless `deflated`.
+    pub size: u64,
+    /// Compressed with deflate, as most ZIP members are.
+    pub deflated: bool,
+}
+
+/// The files in `source` when it is a ZIP or TAR archive.
+pub fn members(source: &Source) -> Result<Option<Vec<Member>>, Error> {
+    let head = try!(source.read(0, 512));
+    if source.codec.is_none() && (head.starts_with(b"PK\x03\x04") || head.starts_with(b"PK\x05\x06")) {
+        return zip(source).map(Some);
+    }
+    if head.len() == 512 && checksum_ok(&head) {
+        let reader = try!(source.open());
+        return tar(reader).map(Some).map_err(|e| source.error(e));
+    }
+    Ok(None)
+}
+
+/// The member called `entry`, or numbered so counting from 1.
+pub fn find(members: &[Member], entry: &str) -> Option<usize> {
+    members.iter().position(|member| member.name == entry).or_else(|| match entry.parse::<usize>() {
+        Ok(n) if 1 <= n && n <= members.len() => Some(n - 1),
+        _ => None,
+    })
+}
+
+fn invalid(what: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, what)
+}
+
+fn u16_at(bytes: &[u8], at: usize) -> u64 {
+    bytes[at] as u64 | (bytes[at + 1] as u64) << 8
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> u64 {
+    u16_at(bytes, at) | u16_at(bytes, at + 2) << 16
+}
+
+/// Members as the central directory at the end of the archive lists them.
+/// ZIP64 archives, over 4 GiB, are not read.
+fn zip(source: &Source) -> Result<Vec<Member>, Error> {
+    let len = try!(source.len());
+    // The end of central directory record, and a comment of up to 64 KiB after it.
+    let tail_start = len.saturating_sub(22 + 0xffff);
+    let tail = try!(source.read(tail_start, len - tail_start));
+    let end = match (0..tail.len().saturating_sub(21)).rev().find(|&i| tail[i..].starts_with(b"PK\x05\x06")) {
+        Some(end) => &tail[end..],
+        None => return Err(source.error(invalid("no end of central directory"))),
+    };
+    let (count, directory_len, directory_at) = (u16_at(end, 10), u32_at(end, 12), u32_at(end, 16));
+    let directory = try!(source.read(directory_at, directory_len));
+    let mut members = vec![];
+    let mut at = 0;
+    for _ in 0..count {
+        if directory.len() < at + 46 || !directory[at..].starts_with(b"PK\x01\x02") {
+            return Err(source.error(invalid("truncated central directory")));
+        }
+        let entry = &directory[at..];
+        let name_len = u16_at(entry, 28) as usize;
+        let next = 46 + name_len + u16_at(entry, 30) as usize + u16_at(entry, 32) as usize;
+        if entry.len() < 46 + name_len {
+            return Err(source.error(invalid("truncated central directory")));
+        }
+        let name = String::from_utf8_lossy(&entry[46..46 + name_len]).into_owned();
+        at += next;
+        if name.ends_with('/') {
+            continue;
+        }
+        // The data comes after the local header, whose extra field may differ in length.
+        let local_at = u32_at(entry, 42);
+        let local = try!(source.read(local_at, 30));
+        if local.len() < 30 || !local.starts_with(b"PK\x03\x04") {
+            return Err(source.error(invalid("bad local header")));
+        }
+        let deflated = match u16_at(entry, 10) {
+            0 => false,
+            8 => true,
+            method => return Err(source.error(invalid(&format!("{} is compressed with method {}, \
+                                                                 neither stored nor deflated", name, method)))),
+        };
+        let len = u32_at(entry, 20);
+        members.push(Member {
+            name: name,
+            offset: local_at + 30 + u16_at(&local, 26) + u16_at(&local, 28),
+            len: len,
+            size: if deflated { u32_at(entry, 24) } else { len },
+            deflated: deflated,
+        });
+    }
+    Ok(members)
+}
+
+/// Whether `header` has the checksum of a TAR header: the sum of its bytes, counting
+/// those of the checksum itself as spaces.
+fn checksum_ok(header: &[u8]) -> bool {
+    let sum: u64 = header.iter().enumerate()
+        .map(|(i, &b)| if 148 <= i && i < 156 { b' ' as u64 } else { b as u64 })
+        .sum();
+    octal(&header[148..156]) == Some(sum)
+}
+
+fn octal(field: &[u8]) -> Option<u64> {
+    let digits: Vec<u8> = field.iter().cloned()
+        .skip_while(|&b| b == b' ')
+        .take_while(|&b| b'0' <= b && b <= b'7')
+        .collect();
+    if digits.is_empty() {
+        return None;
+    }
+    Some(digits.iter().fold(0, |n, &d| n * 8 + (d - b'0') as u64))
+}
+
+/// The bytes of `field` up to the first NUL.
+fn text(field: &[u8]) -> String {
+    let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+/// The `path` of the records of a pax extended header, each `<length> <key>=<value>\n`
+/// with the length counting the whole record.
+fn pax_path(data: &[u8]) -> Option<String> {
+    let mut path = None;
+    let mut at = 0;
+    while at < data.len() {
+        let space = match data[at..].iter().position(|&b| b == b' ') {
+            Some(space) => at + space,
+            None => break,
+        };
+        let len = match String::from_utf8_lossy(&data[at..space]).parse::<usize>() {
+            Ok(len) if space + 1 < at + len && at + len <= data.len() && data[at + len - 1] == b'\n' => len,
+            _ => break,
+        };
+        let record = &data[space + 1..at + len - 1];
+        if record.starts_with(b"path=") {
+            path = Some(String::from_utf8_lossy(&record[5..]).into_owned());
+        }
+        at += len;
+    }
+    path
+}
+
+/// Regular files in order, each after its 512 byte header, with ustar, GNU and pax long names.
+fn tar<R: Read>(mut reader: R) -> io::Result<Vec<Member>> {
+    let mut members = vec![];
+    let mut offset = 0;
+    let mut long_name = None;
+    let mut header = [0u8; 512];
+    loop {
+        match reader.read_exact(&mut header) {
+            Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(members),
+            result => try!(result),
+        }
+        if header.iter().all(|&b| b == 0) {
+            return Ok(members);
+        }
+        if !checksum_ok(&header) {
+            return Err(invalid("bad TAR header checksum"));
+        }
+        let size = try!(octal(&header[124..136]).ok_or(invalid("bad TAR member size")));
+        let padded = (size + 511) / 512 * 512;
+        let mut name = text(&header[..100]);
+        if &header[257..262] == b"ustar" && header[345] != 0 {
+            name = format!("{}/{}", text(&header[345..500]), name);
+        }
+        match header[156] {
+            b'0' | 0 | b'7' => members.push(Member {
+                name: long_name.take().unwrap_or(name),
+                offset: offset + 512,
+                len: size,
+                size: size,
+                deflated: false,
+            }),
+            b'L' | b'x' => {
+                let mut data = vec![];
+                try!(reader.by_ref().take(padded).read_to_end(&mut data));
+                let data = &data[..cmp::min(size as usize, data.len())];
+                if header[156] == b'L' {
+                    long_name = Some(text(data));
+                } else if let Some(path) = pax_path(data) {
+                    long_name = Some(path);
+                }
+                offset += 512 + padded;
+                continue;
+            }
+            _ => (),
+        }
+        try!(io::copy(&mut reader.by_ref().take(padded), &mut io::sink()));
+        offset += 512 + padded;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fixtures;
+    use source::Source;
+    use super::{find, members};
+
+    #[test]
+    fn tar_and_zip() {
+        let (text, strings, random) = (fixtures::read("text.bin"), fixtures::read("strings.bin"),
+                                       fixtures::read("random.bin"));
+        let long = format!("{}/strings.bin", vec!["deep"; 30].concat());
+        let files: Vec<(&str, &[u8])> = vec![("text.bin", &text), (&long, &strings), ("random.bin", &random)];
+        let archives = vec![
+            ("tar", fixtures::tar(&files)),
+            ("zip", fixtures::zip(&[("text.bin", &text, false), (&long, &strings, true), ("random.bin", &random, true)])),
+        ];
+        for (extension, data) in archives {
+            let path = fixtures::write(&format!("members.{}", extension), &data);
+            let source = Source::raw(path.to_str().unwrap());
+            let members = members(&source).unwrap().unwrap();
+            let names: Vec<&str> = members.iter().map(|member| member.name.as_str()).collect();
+            assert_eq!(names, vec!["text.bin", long.as_str(), "random.bin"]);
+            assert_eq!(find(&members, "random.bin"), Some(2));
+            assert_eq!(find(&members, "2"), Some(1));
+            assert_eq!(find(&members, "4"), None);
+            for (member, &(_, bytes)) in members.iter().zip(&files) {
+                let shown = source.member(member.clone());
+                assert_eq!(shown.len().unwrap(), bytes.len() as u64);
+                assert_eq!(shown.read(0, 1 << 20).unwrap(), bytes);
+                assert_eq!(shown.read(100, 50).unwrap(), &bytes[100..150]);
+            }
+        }
+    }
+
+    #[test]
+    fn pax_path() {
+        let long = format!("{}/text.bin", vec!["pax"; 40].concat());
+        let record = format!("path={}\n", long);
+        // The length counts itself, and its own digits.
+        let record = format!("{} {}", record.len() + 4, record);
+        let mut tar = fixtures::tar_header("PaxHeaders/text.bin", record.len(), b'x');
+        tar.extend(record.as_bytes());
+        tar.extend(vec![0; 512 - record.len()]);
+        tar.extend(fixtures::tar(&[("text.bin", b"text")]));
+        let path = fixtures::write("pax.tar", &tar);
+        let members = members(&Source::raw(path.to_str().unwrap())).unwrap().unwrap();
+        assert_eq!(members.iter().map(|member| member.name.as_str()).collect::<Vec<_>>(), vec![long.as_str()]);
+        assert_eq!(members[0].offset, 1536);
+    }
+
+    #[test]
+    fn bad_pax_records() {
+        // Too short to hold a key, and not ending in a newline.
+        for record in &["2 ", "9 path=ab"] {
+            let mut tar = fixtures::tar_header("PaxHeaders/text.bin", record.len(), b'x');
+            tar.extend(record.as_bytes());
+            tar.extend(vec![0; 512 - record.len()]);
+            tar.extend(fixtures::tar(&[("text.bin", b"text")]));
+            let path = fixtures::write("bad-pax.tar", &tar);
+            let members = members(&Source::raw(path.to_str().unwrap())).unwrap().unwrap();
+            assert_eq!(members.iter().map(|member| member.name.as_str()).collect::<Vec<_>>(), vec!["text.bin"]);
+        }
+    }
+
+    #[test]
+    fn zip_methods() {
+        let mut zip = fixtures::zip(&[("text.bin", b"text", false)]);
+        let directory = (0..zip.len()).find(|&i| zip[i..].starts_with(b"PK\x01\x02")).unwrap();
+        // bzip2, which is not read.
+        zip[directory + 10] = 12;
+        let path = fixtures::write("bzip2.zip", &zip);
+        assert!(members(&Source::raw(path.to_str().unwrap())).is_err());
+
+        let path = fixtures::write("empty.zip", &fixtures::zip(&[]));
+        assert_eq!(members(&Source::raw(path.to_str().unwrap())).unwrap(), Some(vec![]));
+    }
+
+    #[test]
+    fn not_an_archive() {
+        assert_eq!(members(&Source::raw(fixtures::path("text.bin").to_str().unwrap())).unwrap(), None);
+    }
+}
@@ -0,0 +1,37 @@
+//! Bit by bit, for packed formats and compressed streams whose patterns don't line up
+//! with bytes: each bit of the input is drawn as a pixel, white when it is set.
+
+/// Which bit of each byte comes first.
+#[derive(Debug, Clone, Copy, PartialEq, RustcDecodable)]
+pub enum Order {
+    Msb,
+    Lsb,
+}
+
+/// One byte per bit of `bytes`, 255 for a set bit and 0 for a clear one, the bits of
+/// each byte in `order`.
+pub fn expand(order: Order, bytes: &[u8]) -> Vec<u8> {
+    let mut expanded = Vec::with_capacity(8 * bytes.len());
+    for &b in bytes {
+        for i in 0..8 {
+            let shift = match order {
+                Order::Msb => 7 - i,
+                Order::Lsb => i,
+            };
+            expanded.push(if b >> shift & 1 == 1 { 255 } else { 0 });
+        }
+    }
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expand, Order};
+
+    #[test]
+    fn orders() {
+        assert_eq!(expand(Order::Msb, &[0x81, 0x40]),
+                   vec![255, 0, 0, 0, 0, 0, 0, 255, 0, 255, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(expand(Order::Lsb, &[0x03]), vec![255, 255, 0, 0, 0, 0, 0, 0]);
+    }
+}
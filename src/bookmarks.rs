@@ -0,0 +1,188 @@
+//! Named offsets and ranges of an input, kept beside it in `<file>.minrs.json`
+//! so that they outlive the session and can be handed to someone else.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+
+use rustc_serialize::json::{self, Json, ToJson};
+
+use error::Error;
+use source::Source;
+
+/// Bumped when the sidecar changes in ways older versions can't read.
+const VERSION: u64 = 1;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bookmark {
+    pub offset: u64,
+    /// Bytes from `offset`, at least one.
+    pub len: u64,
+    pub name: String,
+}
+
+impl ToJson for Bookmark {
+    fn to_json(&self) -> Json {
+        let mut object = BTreeMap::new();
+        object.insert("offset".to_owned(), self.offset.to_json());
+        object.insert("len".to_owned(), self.len.to_json());
+        object.insert("name".to_owned(), self.name.to_json());
+        Json::Object(object)
+    }
+}
+
+/// The bookmarks of one input and the sidecar they are saved to.
+pub struct Project {
+    pub path: PathBuf,
+    /// By offset, then by length.
+    pub bookmarks: Vec<Bookmark>,
+}
+
+impl Project {
+    /// The bookmarks saved for `input`, none when it has no sidecar yet.
+    pub fn load(input: &str) -> Result<Project, Error> {
+        let path = PathBuf::from(format!("{}.minrs.json", input));
+        let mut project = Project{path: path, bookmarks: vec![]};
+        let mut text = String::new();
+        match File::open(&project.path).and_then(|mut f| f.read_to_string(&mut text)) {
+            Ok(_) => (),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(project),
+            Err(e) => return Err(Error::Input(project.name(), e)),
+        }
+        project.bookmarks = try!(parse(&text).map_err(|e| {
+            Error::Input(project.name(), io::Error::new(io::ErrorKind::InvalidData, e))
+        }));
+        project.sort();
+        Ok(project)
+    }
+
+    pub fn save(&self) -> Result<(), Error> {
+        let mut object = BTreeMap::new();
+        object.insert("version".to_owned(), VERSION.to_json());
+        object.insert("bookmarks".to_owned(), self.bookmarks.to_json());
+        let text = format!("{}\n", json::as_pretty_json(&Json::Object(object)));
+        try!(File::create(&self.path)
+             .and_then(|mut f| f.write_all(text.as_bytes()))
+             .map_err(|e| Error::Output(self.name(), e)));
+        debug!("saved {:?} bookmarks to {:?}", self.bookmarks.len(), self.path);
+        Ok(())
+    }
+
+    pub fn add(&mut self, bookmark: Bookmark) {
+        self.bookmarks.push(bookmark);
+        self.sort();
+    }
+
+    fn sort(&mut self) {
+        self.bookmarks.sort_by(|a, b| (a.offset, a.len).cmp(&(b.offset, b.len)));
+    }
+
+    fn name(&self) -> String {
+        self.path.to_string_lossy().into_owned()
+    }
+}
+
+fn parse(text: &str) -> Result<Vec<Bookmark>, String> {
+    let root = try!(Json::from_str(text).map_err(|e| e.to_string()));
+    match root.find("version").and_then(|v| v.as_u64()) {
+        Some(VERSION) => (),
+        Some(v) => return Err(format!("version {} is not supported", v)),
+        None => return Err("no version".to_owned()),
+    }
+    let entries = try!(root.find("bookmarks").and_then(|b| b.as_array()).ok_or("no bookmarks"));
+    let mut bookmarks = vec![];
+    for (i, entry) in entries.iter().enumerate() {
+        let offset = entry.find("offset").and_then(|v| v.as_u64());
+        let len = entry.find("len").and_then(|v| v.as_u64());
+        let name = entry.find("name").and_then(|v| v.as_string());
+        match (offset, len, name) {
+            (Some(offset), Some(len), Some(name)) if len > 0 =>
+                bookmarks.push(Bookmark{offset: offset, len: len, name: name.to_owned()}),
+            _ => return Err(format!("bookmark {} needs an offset, a len of at least 1 and a name", i)),
+        }
+    }
+    Ok(bookmarks)
+}
+
+/// `<offset>` or `<offset>+<len>`, each in decimal or in hex after `0x`.
+pub fn parse_range(s: &str) -> Result<(u64, u64), String> {
+    let mut parts = s.splitn(2, '+');
+    let offset = try!(parse_number(parts.next().unwrap()));
+    let len = try!(parts.next().map_or(Ok(1), parse_number));
+    if len == 0 {
+        return Err(format!("{} is an empty range", s));
+    }
+    Ok((offset, len))
+}
+
+fn parse_number(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let parsed = if s.starts_with("0x") {
+        u64::from_str_radix(&s[2..], 16)
+    } else {
+        s.parse()
+    };
+    parsed.map_err(|_| format!("{:?} is not a number", s))
+}
+
+/// `minrs bookmark`: lists the bookmarks of `input`, or adds one named `name` at `at`,
+/// of the bytes it decompresses to unless `raw`.
+pub fn run(input: &str, at: &str, name: &str, raw: bool) -> Result<(), Error> {
+    let source = if raw { Source::raw(input) } else { try!(Source::sniff(input)) };
+    let mut project = try!(Project::load(&source.project()));
+    if at.is_empty() {
+        for bookmark in &project.bookmarks {
+            println!("{:#x}+{}\t{}", bookmark.offset, bookmark.len, bookmark.name);
+        }
+        return Ok(());
+    }
+    let (offset, len) = try!(parse_range(at).map_err(|e| Error::Range(at.to_owned(), e)));
+    let input_len = try!(source.len());
+    if offset.saturating_add(len) > input_len {
+        return Err(Error::Range(at.to_owned(), format!("{} is only {} bytes long", input, input_len)));
+    }
+    project.add(Bookmark{offset: offset, len: len, name: name.to_owned()});
+    project.save()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use fixtures;
+    use super::{parse, parse_range, Bookmark, Project};
+
+    #[test]
+    fn saved() {
+        // A copy of a fixture, so that its sidecar is written under target/.
+        let path = fixtures::write("bookmarks-saved.bin", &fixtures::read("strings.bin"));
+        let input = path.to_str().unwrap();
+        let _ = fs::remove_file(format!("{}.minrs.json", input));
+
+        let mut project = Project::load(input).unwrap();
+        assert!(project.bookmarks.is_empty());
+        project.add(Bookmark{offset: 102, len: 38, name: "System32".to_owned()});
+        project.add(Bookmark{offset: 37, len: 14, name: "GetProcAddress".to_owned()});
+        project.add(Bookmark{offset: 400, len: 1, name: "one byte".to_owned()});
+        project.save().unwrap();
+        let saved = Project::load(input).unwrap().bookmarks;
+        assert_eq!(saved.iter().map(|b| b.offset).collect::<Vec<_>>(), vec![37, 102, 400]);
+        assert_eq!(saved, project.bookmarks);
+    }
+
+    #[test]
+    fn bad_sidecars() {
+        assert!(parse("{\"bookmarks\": []}").is_err());
+        assert!(parse("{\"version\": 99, \"bookmarks\": []}").is_err());
+        assert!(parse("not json").is_err());
+    }
+
+    #[test]
+    fn ranges() {
+        assert_eq!(parse_range("0x10+4"), Ok((16, 4)));
+        assert_eq!(parse_range("300"), Ok((300, 1)));
+        assert!(parse_range("12+0").is_err());
+        assert!(parse_range("x").is_err());
+    }
+}
@@ -0,0 +1,113 @@
+//! What can be done with a range of bytes selected in the window: say where it is,
+//! dump it in hex or cut it out into a file of its own.
+
+use std::fs::OpenOptions;
+use std::io::{self, BufWriter, Read, Write};
+
+use error::Error;
+use source::Source;
+
+/// Where the `len` bytes from `offset` are, as `minrs bookmark` and `dd` take it.
+pub fn offsets(offset: u64, len: u64) -> String {
+    format!("{:#x}+{} (dd bs=1 skip={} count={})", offset, len, offset, len)
+}
+
+/// Opens `source` at `offset`, no more than `len` bytes to read from there.
+fn open(source: &Source, offset: u64, len: u64) -> Result<io::Take<Box<Read>>, Error> {
+    Ok(try!(source.open_at(offset)).take(len))
+}
+
+/// Writes the `len` bytes of `source` from `offset` to `out` as `hexdump -C` does.
+pub fn hexdump<W: Write>(source: &Source, offset: u64, len: u64, out: &mut W) -> Result<(), Error> {
+    let mut reader = try!(open(source, offset, len));
+    let mut line = [0u8; 16];
+    let mut at = offset;
+    loop {
+        let read = try!(read_line(&mut reader, &mut line).map_err(|e| source.error(e)));
+        if read == 0 {
+            break;
+        }
+        try!(dump_line(out, at, &line[..read]).map_err(|e| Error::Output("stdout".to_owned(), e)));
+        at += read as u64;
+    }
+    try!(writeln!(out, "{:08x}", at).map_err(|e| Error::Output("stdout".to_owned(), e)));
+    Ok(())
+}
+
+/// Fills `line` but at the end of `reader`, unlike a single `read`.
+fn read_line<R: Read>(reader: &mut R, line: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < line.len() {
+        match try!(reader.read(&mut line[filled..])) {
+            0 => break,
+            read => filled += read,
+        }
+    }
+    Ok(filled)
+}
+
+fn dump_line<W: Write>(out: &mut W, at: u64, bytes: &[u8]) -> io::Result<()> {
+    try!(write!(out, "{:08x} ", at));
+    for i in 0..16 {
+        if i == 8 {
+            try!(write!(out, " "));
+        }
+        match bytes.get(i) {
+            Some(b) => try!(write!(out, " {:02x}", b)),
+            None => try!(write!(out, "   ")),
+        }
+    }
+    let text: String = bytes.iter()
+        .map(|&b| if 0x20 <= b && b <= 0x7e { b as char } else { '.' })
+        .collect();
+    writeln!(out, "  |{}|", text)
+}
+
+/// Copies the `len` bytes of `source` from `offset` into a new file at `out`.
+/// Fails rather than overwrite anything.
+pub fn write(source: &Source, offset: u64, len: u64, out: &str) -> Result<u64, Error> {
+    let mut reader = try!(open(source, offset, len));
+    let file = try!(OpenOptions::new().write(true).create_new(true).open(out)
+                    .map_err(|e| Error::Output(out.to_owned(), e)));
+    let mut writer = BufWriter::new(file);
+    let copied = try!(io::copy(&mut reader, &mut writer).map_err(|e| Error::Output(out.to_owned(), e)));
+    try!(writer.flush().map_err(|e| Error::Output(out.to_owned(), e)));
+    debug!("wrote {:?} bytes to {:?}", copied, out);
+    Ok(copied)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::io::Read;
+
+    use fixtures;
+    use source::Source;
+    use super::{hexdump, offsets, write};
+
+    #[test]
+    fn dump() {
+        let path = fixtures::path("strings.bin");
+        let input = Source::raw(path.to_str().unwrap());
+        let mut dump = vec![];
+        hexdump(&input, 0x20, 20, &mut dump).unwrap();
+        assert_eq!(String::from_utf8(dump).unwrap(),
+                   "00000020  80 35 ff ff 9e 47 65 74  50 72 6f 63 41 64 64 72  |.5...GetProcAddr|\n\
+                    00000030  65 73 73 00                                       |ess.|\n\
+                    00000034\n");
+        assert_eq!(offsets(37, 14), "0x25+14 (dd bs=1 skip=37 count=14)");
+    }
+
+    #[test]
+    fn carve() {
+        let path = fixtures::path("strings.bin");
+        let input = Source::raw(path.to_str().unwrap());
+        let out = fixtures::out("carve.bin");
+        assert_eq!(write(&input, 37, 14, out.to_str().unwrap()).unwrap(), 14);
+        let mut carved = vec![];
+        File::open(&out).unwrap().read_to_end(&mut carved).unwrap();
+        assert_eq!(carved, b"GetProcAddress");
+        // Never over a file already there.
+        assert!(write(&input, 37, 14, out.to_str().unwrap()).is_err());
+    }
+}
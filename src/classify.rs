@@ -0,0 +1,105 @@
+//! What a block of bytes likely holds, told from how its byte values are distributed,
+//! for `Mode::Class` to tint each block of the picture by it.
+
+use entropy;
+use error::Error;
+use source::Source;
+
+/// Bytes per block unless asked otherwise: enough for the statistics to mean something.
+pub const BLOCK: usize = 1024;
+
+/// Entropy, over 8 bits, from which a block is compressed or random.
+const DENSE: f64 = 0.85;
+
+/// Chi-square against evenly distributed byte values past which a block is too uneven to
+/// be random: three standard deviations above the 255 expected with 255 degrees of freedom.
+const UNEVEN: f64 = 255.0 + 3.0 * 22.6;
+
+/// Share of tabs, line breaks and printable ASCII from which a block is text.
+const TEXT: f64 = 0.9;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Class {
+    /// One byte value repeated, such as padding.
+    Blank,
+    Text,
+    /// Machine code and other structured binary: everything else.
+    Code,
+    /// Dense but unevenly distributed, as compressed data is.
+    Compressed,
+    /// As evenly distributed as random bytes, as encrypted data is.
+    Random,
+}
+
+pub fn classify(bytes: &[u8]) -> Class {
+    let counts = entropy::histogram(bytes);
+    let total = bytes.len() as u64;
+    if counts.iter().any(|&count| count == total) {
+        return Class::Blank;
+    }
+    let text: u64 = counts.iter().enumerate()
+        .filter(|&(b, _)| b == 9 || b == 10 || b == 13 || (32 <= b && b <= 126))
+        .map(|(_, &count)| count)
+        .sum();
+    if text as f64 >= TEXT * total as f64 {
+        return Class::Text;
+    }
+    if entropy::entropy(&counts, total) < DENSE {
+        return Class::Code;
+    }
+    let expected = total as f64 / 256f64;
+    let chi_square: f64 = counts.iter()
+        .map(|&count| (count as f64 - expected) * (count as f64 - expected) / expected)
+        .sum();
+    if chi_square > UNEVEN { Class::Compressed } else { Class::Random }
+}
+
+/// The class of the block of `block` bytes, counted from the start of `source`, that each
+/// of the `len` bytes of `source` from `offset` is in.
+pub fn page(source: &Source, offset: u64, len: usize, block: usize) -> Result<Vec<Class>, Error> {
+    let size = block as u64;
+    let start = offset / size * size;
+    let end = (offset + len as u64 + size - 1) / size * size;
+    let bytes = try!(source.read(start, end - start));
+    let classes: Vec<Class> = bytes.chunks(block).map(classify).collect();
+    let skip = (offset - start) as usize;
+    Ok((skip..skip + len).map(|i| classes[i / block]).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use fixtures;
+    use source::Source;
+    use super::{classify, page, Class};
+
+    #[test]
+    fn classes() {
+        let random = fixtures::read("random.bin");
+        let text = fixtures::read("text.bin");
+        assert_eq!(classify(&[0; 1024]), Class::Blank);
+        assert_eq!(classify(&[0xff; 1024]), Class::Blank);
+        assert_eq!(classify(&text[..1024]), Class::Text);
+        // The text fixture ends in binary.
+        assert_eq!(classify(&text[1024..2048]), Class::Code);
+        assert_eq!(classify(&random[..1024]), Class::Random);
+        // Random bytes with the low bit cleared in every other one: dense but uneven.
+        let skewed: Vec<u8> = random[..1024].iter().enumerate()
+            .map(|(i, &b)| if i % 2 == 0 { b & 0xfe } else { b })
+            .collect();
+        assert_eq!(classify(&skewed), Class::Compressed);
+        // Little-endian counters, as in tables of offsets.
+        let counters: Vec<u8> = (0..256u32).flat_map(|i| vec![i as u8, (i >> 8) as u8, 0, 0]).collect();
+        assert_eq!(classify(&counters), Class::Code);
+    }
+
+    #[test]
+    fn pages() {
+        let mut bytes = vec![0; 16];
+        bytes.extend_from_slice(b"text, text and more text");
+        let path = fixtures::write("classify.bin", &bytes);
+        let source = Source::raw(path.to_str().unwrap());
+        assert_eq!(page(&source, 12, 8, 16).unwrap(),
+                   vec![Class::Blank, Class::Blank, Class::Blank, Class::Blank,
+                        Class::Text, Class::Text, Class::Text, Class::Text]);
+    }
+}
@@ -0,0 +1,54 @@
+//! The CPU renderer: needs no GPU or display at all. It draws one frame,
+//! either in the terminal or only in memory to be saved or checked.
+
+use error::Error;
+use raster::Image;
+use render::{Input, Renderer, Scene};
+use term::{self, Graphics};
+
+pub struct Cpu {
+    width: u32,
+    height: u32,
+    /// Where to show frames, if anywhere.
+    graphics: Option<Graphics>,
+    image: Image,
+}
+
+impl Cpu {
+    /// As many pixels as fit in the terminal, drawn there with `graphics`.
+    pub fn term(graphics: Graphics) -> Cpu {
+        let (width, height) = term::size(graphics);
+        Cpu{width: width, height: height, graphics: Some(graphics), image: Image::new(0, 0)}
+    }
+
+    pub fn offscreen(width: u32, height: u32) -> Cpu {
+        Cpu{width: width, height: height, graphics: None, image: Image::new(0, 0)}
+    }
+}
+
+impl Renderer for Cpu {
+    fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    fn load(&mut self, scene: &Scene) -> Result<(), Error> {
+        self.image = scene.rasterize(self.width, self.height);
+        Ok(())
+    }
+
+    fn draw(&mut self) -> Result<(), Error> {
+        match self.graphics {
+            Some(graphics) => term::show(&self.image, graphics),
+            None => Ok(()),
+        }
+    }
+
+    /// There is nobody to ask, so one frame is all there is.
+    fn input(&mut self) -> Vec<Input> {
+        vec![Input::Quit]
+    }
+
+    fn frame(&self) -> Result<Image, Error> {
+        Ok(self.image.clone())
+    }
+}
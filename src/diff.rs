@@ -0,0 +1,9 @@
+//! `minrs diff <a> <b>`: two files side by side, scrolled together,
+//! or a single pane where the bytes that differ light up.
+
+/// Number of offsets where the two buffers disagree, bytes past the end of
+/// the shorter one counting as zero as they do on screen.
+pub fn differing(a: &[u8], b: &[u8]) -> usize {
+    let len = ::std::cmp::max(a.len(), b.len());
+    (0..len).filter(|&i| a.get(i).unwrap_or(&0) != b.get(i).unwrap_or(&0)).count()
+}
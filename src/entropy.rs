@@ -0,0 +1,191 @@
+//! Shannon entropy of each block of an input, as `binwalk -E` plots it: in a strip
+//! beside the panes where offsets go down and entropy to the right, or as CSV.
+
+use std::cmp;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+
+use error::Error;
+use palette::Rgb;
+use raster::Image;
+use source::Source;
+
+/// Pixels across the strip.
+pub const WIDTH: u32 = 64;
+
+/// The colours of `frag_entropy_2d_140.glsl`.
+const LINE: Rgb = [0, 192, 255];
+const FILL: Rgb = [0, 64, 96];
+const VISIBLE: Rgb = [48, 48, 48];
+const BACKGROUND: Rgb = [0, 0, 0];
+
+/// Entropy of each `block` bytes of `reader`, the last block maybe shorter, from 0 when
+/// all bytes of a block are the same to 1 when all 256 values are equally frequent.
+pub fn blocks<R: Read>(mut reader: R, block: usize) -> io::Result<Vec<f64>> {
+    let mut series = vec![];
+    let mut buffer = vec![0u8; block];
+    loop {
+        let mut filled = 0;
+        while filled < block {
+            match try!(reader.read(&mut buffer[filled..])) {
+                0 => break,
+                read => filled += read,
+            }
+        }
+        if filled == 0 {
+            return Ok(series);
+        }
+        series.push(entropy(&histogram(&buffer[..filled]), filled as u64));
+    }
+}
+
+/// How many times each byte value occurs in `bytes`.
+pub fn histogram(bytes: &[u8]) -> [u64; 256] {
+    let mut counts = [0u64; 256];
+    for &b in bytes {
+        counts[b as usize] += 1;
+    }
+    counts
+}
+
+/// Entropy over 8 bits of `total` bytes occurring `counts` times each.
+pub fn entropy(counts: &[u64; 256], total: u64) -> f64 {
+    let total = total as f64;
+    let bits: f64 = counts.iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| count as f64 / total * (total / count as f64).log2())
+        .sum();
+    bits / 8f64
+}
+
+/// Writes one `offset,entropy` line per block of `block` bytes.
+pub fn write_csv(path: &str, block: usize, series: &[f64]) -> Result<(), Error> {
+    try!(csv(path, block, series).map_err(|e| Error::Output(path.to_owned(), e)));
+    debug!("wrote entropy of {:?} blocks to {:?}", series.len(), path);
+    Ok(())
+}
+
+fn csv(path: &str, block: usize, series: &[f64]) -> io::Result<()> {
+    let mut out = BufWriter::new(try!(File::create(path)));
+    try!(writeln!(out, "offset,entropy"));
+    for (i, e) in series.iter().enumerate() {
+        try!(writeln!(out, "{},{:.6}", i * block, e));
+    }
+    out.flush()
+}
+
+/// How far right the line is on each of `rows` rows, each showing an equal share of the
+/// `len` bytes that `series` covers: the mean of the blocks starting in the row, or
+/// with fewer blocks than rows, the block the row starts in.
+pub fn levels(series: &[f64], block: usize, len: u64, rows: u32) -> Vec<u8> {
+    let block = block as u64;
+    (0..rows as u64).map(|row| {
+        let start = row * len / rows as u64;
+        let end = (row + 1) * len / rows as u64;
+        let (mut first, mut last) = ((start + block - 1) / block, (end + block - 1) / block);
+        if first >= last {
+            first = start / block;
+            last = first + 1;
+        }
+        let last = cmp::min(last, series.len() as u64);
+        if first >= last {
+            return 0;
+        }
+        let blocks = &series[first as usize..last as usize];
+        let mean = blocks.iter().sum::<f64>() / blocks.len() as f64;
+        (mean * (WIDTH - 1) as f64).round() as u8
+    }).collect()
+}
+
+/// Pixel for pixel what `frag_entropy_2d_140.glsl` draws for `levels`, a row each,
+/// the rows from `visible.0` to `visible.1` being those on screen.
+pub fn graph(levels: &[u8], visible: (u32, u32)) -> Image {
+    let mut image = Image::new(WIDTH, levels.len() as u32);
+    for (row, &level) in levels.iter().enumerate() {
+        let row = row as u32;
+        // The line goes across from where the row above left it.
+        let previous = if row == 0 { level } else { levels[row as usize - 1] };
+        let (from, to) = (cmp::min(previous, level) as u32, cmp::max(previous, level) as u32);
+        for column in 0..WIDTH {
+            let c = if from <= column && column <= to {
+                LINE
+            } else if column < from {
+                FILL
+            } else if visible.0 <= row && row <= visible.1 {
+                VISIBLE
+            } else {
+                BACKGROUND
+            };
+            image.set(column, row, c);
+        }
+    }
+    image
+}
+
+/// `minrs entropy`: writes the entropy of each `block` bytes of `input`, or of what it
+/// decompresses to unless `raw`, to `out` as CSV.
+pub fn run(input: &str, block: usize, out: &str, raw: bool) -> Result<(), Error> {
+    let source = if raw { Source::raw(input) } else { try!(Source::sniff(input)) };
+    let series = try!(blocks(try!(source.open()), block).map_err(|e| source.error(e)));
+    write_csv(out, block, &series)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::io::{Read, Write};
+
+    use flate2;
+
+    use fixtures;
+    use super::{blocks, run};
+
+    /// Text, then random bytes, then zeros: entropy in the middle, then high, then none.
+    fn bytes() -> Vec<u8> {
+        let mut bytes = fixtures::read("text.bin");
+        bytes.extend_from_slice(&fixtures::read("random.bin")[..4096]);
+        bytes.extend_from_slice(&[0; 2048]);
+        bytes
+    }
+
+    fn csv(input: &str, raw: bool) -> String {
+        let csv = fixtures::out(&format!("{}.csv", input.rsplit('/').next().unwrap()));
+        run(input, 1024, csv.to_str().unwrap(), raw).unwrap();
+        let mut text = String::new();
+        File::open(&csv).unwrap().read_to_string(&mut text).unwrap();
+        text
+    }
+
+    #[test]
+    fn series() {
+        let all: Vec<u8> = (0..256).map(|b| b as u8).collect();
+        assert_eq!(blocks(&all[..], 256).unwrap(), vec![1f64]);
+        assert_eq!(blocks(&all[..], 128).unwrap(), vec![7f64 / 8f64, 7f64 / 8f64]);
+        let series = blocks(&bytes()[..], 1024).unwrap();
+        assert_eq!(series.len(), 8);
+        assert_eq!(series[7], 0f64);
+    }
+
+    #[test]
+    fn csv_file() {
+        let path = fixtures::write("entropy-csv.bin", &bytes());
+        let text = csv(path.to_str().unwrap(), false);
+        assert_eq!(text.lines().count(), 9);
+        assert!(text.starts_with("offset,entropy\n0,0."));
+        assert!(text.ends_with("\n7168,0.000000\n"));
+    }
+
+    #[test]
+    fn compressed() {
+        // A compressed input has the entropy of what it decompresses to, unless raw.
+        let bytes = bytes();
+        let path = fixtures::write("entropy-plain.bin", &bytes);
+        let gz = fixtures::out("entropy-plain.bin.gz");
+        let mut gzip = flate2::write::GzEncoder::new(File::create(&gz).unwrap(), flate2::Compression::default());
+        gzip.write_all(&bytes).unwrap();
+        gzip.finish().unwrap();
+        let text = csv(path.to_str().unwrap(), false);
+        assert_eq!(csv(gz.to_str().unwrap(), false), text);
+        assert!(csv(gz.to_str().unwrap(), true) != text);
+    }
+}
@@ -0,0 +1,176 @@
+//! Everything that can go wrong, how to say it and with which exit code.
+
+use std::{error, fmt, io};
+
+use glium;
+use glium::program::{ProgramChooserCreationError, ProgramCreationError};
+
+#[derive(Debug)]
+pub enum Error {
+    /// The input can't be opened or read.
+    Input(String, io::Error),
+    /// The input has no bytes to show.
+    Empty(String),
+    /// There is no room to lay out a single byte in this many pixels.
+    Layout(u32, u32),
+    /// A shader failed to compile or link.
+    Shader(ProgramCreationError),
+    /// The OpenGL context doesn't run GLSL 1.40.
+    Unsupported(glium::Version),
+    /// No window or OpenGL context could be created.
+    Context(glium::GliumCreationError<glium::glutin::CreationError>),
+    Buffer(glium::vertex::BufferCreationError),
+    Texture(glium::texture::TextureCreationError),
+    Framebuffer(glium::framebuffer::ValidationError),
+    Draw(glium::DrawError),
+    SwapBuffers(glium::SwapBuffersError),
+    /// The search pattern, and why it can't be searched for.
+    Pattern(String, String),
+    /// The offset or range, and why it is not one of the input.
+    Range(String, String),
+    /// The result can't be written out.
+    Output(String, io::Error),
+    /// This many of that many pixels drawn differ from the CPU rasterization, the first one here.
+    Check(usize, usize, (u32, u32)),
+}
+
+impl Error {
+    /// Exit status of the binary, also listed in its usage.
+    pub fn exit_code(&self) -> i32 {
+        match *self {
+            Error::Pattern(..) | Error::Range(..) => 1,
+            Error::Input(..) | Error::Empty(..) => 2,
+            Error::Layout(..) => 3,
+            Error::Shader(..) => 4,
+            Error::Unsupported(..) | Error::Context(..) | Error::Buffer(..) |
+            Error::Texture(..) | Error::Framebuffer(..) | Error::Draw(..) | Error::SwapBuffers(..) => 5,
+            Error::Output(..) => 6,
+            Error::Check(..) => 7,
+        }
+    }
+
+    /// For `program!` results, which only know they failed for lack of a GLSL version.
+    pub fn from_program(e: ProgramChooserCreationError, version: glium::Version) -> Error {
+        match e {
+            ProgramChooserCreationError::NoVersion => Error::Unsupported(version),
+            ProgramChooserCreationError::ProgramCreationError(e) => Error::Shader(e),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Input(ref path, ref e) => write!(f, "cannot read {}: {}", path, e),
+            Error::Empty(ref path) => write!(f, "{} is empty, there is nothing to show", path),
+            Error::Layout(width, height) =>
+                write!(f, "cannot lay bytes out in {}x{} pixels", width, height),
+            Error::Shader(ProgramCreationError::CompilationError(ref log)) =>
+                write!(f, "shader failed to compile:\n{}", log),
+            Error::Shader(ProgramCreationError::LinkingError(ref log)) =>
+                write!(f, "shaders failed to link:\n{}", log),
+            Error::Shader(ref e) => write!(f, "cannot create shaders: {}", e),
+            Error::Unsupported(ref version) =>
+                write!(f, "OpenGL {:?} does not support GLSL 1.40 shaders", version),
+            Error::Context(ref e) => write!(f, "cannot create an OpenGL context: {}", e),
+            Error::Buffer(ref e) => write!(f, "cannot create vertex buffer: {}", e),
+            Error::Texture(ref e) => write!(f, "cannot create texture: {}", e),
+            Error::Framebuffer(ref e) => write!(f, "cannot create framebuffer: {}", e),
+            Error::Draw(ref e) => write!(f, "cannot draw: {}", e),
+            Error::SwapBuffers(ref e) => write!(f, "cannot show frame: {}", e),
+            Error::Pattern(ref pattern, ref e) => write!(f, "cannot search for {}: {}", pattern, e),
+            Error::Range(ref range, ref e) => write!(f, "invalid range {}: {}", range, e),
+            Error::Output(ref path, ref e) => write!(f, "cannot write {}: {}", path, e),
+            Error::Check(found, total, (x, y)) =>
+                write!(f, "{} of {} pixels differ from the CPU rasterization, the first at ({}, {})",
+                       found, total, x, y),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        match *self {
+            Error::Input(..) => "cannot read input",
+            Error::Empty(..) => "empty input",
+            Error::Layout(..) => "nothing to lay out",
+            Error::Shader(..) => "cannot create shaders",
+            Error::Unsupported(..) => "GLSL 1.40 not supported",
+            Error::Context(..) => "cannot create OpenGL context",
+            Error::Buffer(..) => "cannot create vertex buffer",
+            Error::Texture(..) => "cannot create texture",
+            Error::Framebuffer(..) => "cannot create framebuffer",
+            Error::Draw(..) => "cannot draw",
+            Error::SwapBuffers(..) => "cannot show frame",
+            Error::Pattern(..) => "invalid search pattern",
+            Error::Range(..) => "invalid range",
+            Error::Output(..) => "cannot write output",
+            Error::Check(..) => "pixels differ from the CPU rasterization",
+        }
+    }
+}
+
+impl From<glium::GliumCreationError<glium::glutin::CreationError>> for Error {
+    fn from(e: glium::GliumCreationError<glium::glutin::CreationError>) -> Error {
+        Error::Context(e)
+    }
+}
+
+impl From<glium::vertex::BufferCreationError> for Error {
+    fn from(e: glium::vertex::BufferCreationError) -> Error {
+        Error::Buffer(e)
+    }
+}
+
+impl From<glium::texture::TextureCreationError> for Error {
+    fn from(e: glium::texture::TextureCreationError) -> Error {
+        Error::Texture(e)
+    }
+}
+
+impl From<glium::framebuffer::ValidationError> for Error {
+    fn from(e: glium::framebuffer::ValidationError) -> Error {
+        Error::Framebuffer(e)
+    }
+}
+
+impl From<glium::DrawError> for Error {
+    fn from(e: glium::DrawError) -> Error {
+        Error::Draw(e)
+    }
+}
+
+impl From<glium::SwapBuffersError> for Error {
+    fn from(e: glium::SwapBuffersError) -> Error {
+        Error::SwapBuffers(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use glium;
+    use glium::program::ProgramCreationError;
+
+    use super::Error;
+
+    #[test]
+    fn exit_codes() {
+        let io_error = || io::Error::new(io::ErrorKind::NotFound, "gone");
+        let codes: Vec<i32> = vec![
+            Error::Pattern("hex:4".to_owned(), "odd".to_owned()),
+            Error::Range("0+0".to_owned(), "empty".to_owned()),
+            Error::Input("in".to_owned(), io_error()),
+            Error::Empty("in".to_owned()),
+            Error::Layout(0, 0),
+            Error::Shader(ProgramCreationError::CompilationNotSupported),
+            Error::Unsupported(glium::Version(glium::Api::Gl, 2, 1)),
+            Error::Draw(glium::DrawError::NoDepthBuffer),
+            Error::SwapBuffers(glium::SwapBuffersError::ContextLost),
+            Error::Output("out".to_owned(), io_error()),
+            Error::Check(1, 4, (0, 0)),
+        ].iter().map(Error::exit_code).collect();
+        assert_eq!(codes, vec![1, 1, 2, 2, 3, 4, 5, 5, 5, 6, 7]);
+    }
+}
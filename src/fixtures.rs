@@ -0,0 +1,206 @@
+//! What the tests share: the fixtures in `tests/fixtures/`, the files they make from
+//! them under `target/fixtures/`, and a renderer that plays back what the user asks for.
+
+use std::cmp;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+use bzip2;
+use flate2;
+use xz2;
+use zstd;
+
+use cpu::Cpu;
+use error::Error;
+use raster::Image;
+use render::{Input, Renderer, Scene};
+use source::Codec;
+
+pub fn root() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+/// Where `tests/fixtures/<name>` is.
+pub fn path(name: &str) -> PathBuf {
+    root().join("tests/fixtures").join(name)
+}
+
+/// The bytes of `tests/fixtures/<name>`.
+pub fn read(name: &str) -> Vec<u8> {
+    let mut buffer = vec![];
+    File::open(path(name)).unwrap().read_to_end(&mut buffer).unwrap();
+    buffer
+}
+
+/// Where a test makes `target/fixtures/<name>`, nothing left there from the last run.
+pub fn out(name: &str) -> PathBuf {
+    let out = root().join("target/fixtures").join(name);
+    fs::create_dir_all(out.parent().unwrap()).unwrap();
+    let _ = fs::remove_file(&out);
+    out
+}
+
+/// Writes `bytes` to `target/fixtures/<name>` and says where.
+pub fn write(name: &str, bytes: &[u8]) -> PathBuf {
+    let out = out(name);
+    File::create(&out).unwrap().write_all(bytes).unwrap();
+    out
+}
+
+/// Writes a sparse `target/fixtures/<name>` of 2 MiB: random bytes, then a hole, then
+/// random bytes again and a hole to the end. Says where, and what it reads as.
+pub fn sparse(name: &str) -> (PathBuf, Vec<u8>) {
+    let random = read("random.bin");
+    let out = out(name);
+    let mut f = File::create(&out).unwrap();
+    f.write_all(&random[..4096]).unwrap();
+    f.seek(SeekFrom::Start(1 << 20)).unwrap();
+    f.write_all(&random[4096..8192]).unwrap();
+    f.set_len(2 << 20).unwrap();
+    let mut bytes = random[..4096].to_vec();
+    bytes.resize(1 << 20, 0);
+    bytes.extend(&random[4096..8192]);
+    bytes.resize(2 << 20, 0);
+    (out, bytes)
+}
+
+/// `bytes` compressed with each codec, and the extension a file of it has.
+pub fn compressed(bytes: &[u8]) -> Vec<(&'static str, Codec, Vec<u8>)> {
+    let mut gzip = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+    gzip.write_all(bytes).unwrap();
+    let mut xz = xz2::write::XzEncoder::new(vec![], 6);
+    xz.write_all(bytes).unwrap();
+    let mut bzip2 = bzip2::write::BzEncoder::new(vec![], bzip2::Compression::Default);
+    bzip2.write_all(bytes).unwrap();
+    vec![
+        ("gz", Codec::Gzip, gzip.finish().unwrap()),
+        ("xz", Codec::Xz, xz.finish().unwrap()),
+        ("zst", Codec::Zstd, zstd::encode_all(bytes, 3).unwrap()),
+        ("bz2", Codec::Bzip2, bzip2.finish().unwrap()),
+    ]
+}
+
+/// A TAR header for a regular file, or a GNU long name when `kind` is `b'L'`.
+pub fn tar_header(name: &str, size: usize, kind: u8) -> Vec<u8> {
+    let mut header = vec![0u8; 512];
+    header[..name.len()].copy_from_slice(name.as_bytes());
+    header[124..136].copy_from_slice(format!("{:011o}\0", size).as_bytes());
+    header[156] = kind;
+    header[257..263].copy_from_slice(b"ustar\0");
+    for b in &mut header[148..156] {
+        *b = b' ';
+    }
+    let sum: u32 = header.iter().map(|&b| b as u32).sum();
+    header[148..156].copy_from_slice(format!("{:06o}\0 ", sum).as_bytes());
+    header
+}
+
+/// A TAR archive of `files`, with a GNU long name for names past 100 bytes.
+pub fn tar(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut tar = vec![];
+    for &(name, data) in files {
+        if name.len() > 100 {
+            tar.extend(tar_header("././@LongLink", name.len() + 1, b'L'));
+            tar.extend(name.as_bytes());
+            tar.extend(vec![0; 512 - name.len() % 512]);
+        }
+        tar.extend(tar_header(&name[..cmp::min(name.len(), 100)], data.len(), b'0'));
+        tar.extend(data);
+        tar.extend(vec![0; (512 - data.len() % 512) % 512]);
+    }
+    tar.extend(vec![0; 1024]);
+    tar
+}
+
+/// A ZIP archive of `files`, deflated where asked, without the CRCs nothing here checks.
+pub fn zip(files: &[(&str, &[u8], bool)]) -> Vec<u8> {
+    fn le(bytes: &mut Vec<u8>, v: usize, len: usize) {
+        bytes.extend((0..len).map(|i| (v >> (8 * i)) as u8));
+    }
+    let (mut zip, mut directory) = (vec![], vec![]);
+    for &(name, data, deflated) in files {
+        let stored = if deflated {
+            let mut deflate = flate2::write::DeflateEncoder::new(vec![], flate2::Compression::default());
+            deflate.write_all(data).unwrap();
+            deflate.finish().unwrap()
+        } else {
+            data.to_vec()
+        };
+        let method = if deflated { 8 } else { 0 };
+        directory.extend(b"PK\x01\x02");
+        le(&mut directory, 20, 2);
+        le(&mut directory, 20, 2);
+        le(&mut directory, 0, 2);
+        le(&mut directory, method, 2);
+        directory.extend(&[0; 8]);
+        le(&mut directory, stored.len(), 4);
+        le(&mut directory, data.len(), 4);
+        le(&mut directory, name.len(), 2);
+        directory.extend(&[0; 12]);
+        le(&mut directory, zip.len(), 4);
+        directory.extend(name.as_bytes());
+        // An extra field in the local header only, as some archivers write.
+        zip.extend(b"PK\x03\x04");
+        le(&mut zip, 20, 2);
+        le(&mut zip, 0, 2);
+        le(&mut zip, method, 2);
+        zip.extend(&[0; 8]);
+        le(&mut zip, stored.len(), 4);
+        le(&mut zip, data.len(), 4);
+        le(&mut zip, name.len(), 2);
+        le(&mut zip, 4, 2);
+        zip.extend(name.as_bytes());
+        zip.extend(b"\xfe\xca\x00\x00");
+        zip.extend(stored);
+    }
+    let directory_at = zip.len();
+    zip.extend(&directory);
+    zip.extend(b"PK\x05\x06");
+    le(&mut zip, 0, 4);
+    le(&mut zip, files.len(), 2);
+    le(&mut zip, files.len(), 2);
+    le(&mut zip, directory.len(), 4);
+    le(&mut zip, directory_at, 4);
+    le(&mut zip, 0, 2);
+    zip
+}
+
+/// The CPU renderer, handed one batch of inputs a frame until there are none left,
+/// then told to quit.
+pub struct Script {
+    pub cpu: Cpu,
+    pub inputs: Vec<Vec<Input>>,
+}
+
+impl Script {
+    pub fn new(width: u32, height: u32, inputs: Vec<Vec<Input>>) -> Script {
+        Script{cpu: Cpu::offscreen(width, height), inputs: inputs}
+    }
+}
+
+impl Renderer for Script {
+    fn size(&self) -> (u32, u32) {
+        self.cpu.size()
+    }
+
+    fn load(&mut self, scene: &Scene) -> Result<(), Error> {
+        self.cpu.load(scene)
+    }
+
+    fn draw(&mut self) -> Result<(), Error> {
+        self.cpu.draw()
+    }
+
+    fn input(&mut self) -> Vec<Input> {
+        if self.inputs.is_empty() {
+            vec![Input::Quit]
+        } else {
+            self.inputs.remove(0)
+        }
+    }
+
+    fn frame(&self) -> Result<Image, Error> {
+        self.cpu.frame()
+    }
+}
@@ -0,0 +1,12 @@
+#version 140
+
+uniform vec3 window;
+uniform sampler2D tex;
+
+in vec2 pos;
+out vec4 color;
+
+// Only drawn on the outline of a bookmark, whatever the byte.
+void main() {
+    color = vec4(1, 0, 1, 1);
+}
@@ -0,0 +1,24 @@
+#version 140
+
+uniform vec3 window;
+uniform sampler2D tex;
+// The class of the block of each byte, as in `classify::Class`.
+uniform sampler2D classes;
+
+in vec2 pos;
+out vec4 color;
+
+vec3 tint(in float k) {
+    if (k == 1.0) return vec3(16.0, 64.0, 127.0);   // text
+    if (k == 2.0) return vec3(32.0, 112.0, 32.0);   // code
+    if (k == 3.0) return vec3(127.0, 112.0, 0.0);   // compressed
+    if (k == 4.0) return vec3(127.0, 0.0, 32.0);    // random
+    return vec3(0.0, 0.0, 0.0);                     // blank
+}
+
+// Whole numbers over 255 all along, for the CPU to get the very same colours.
+void main() {
+    float half_byte = floor(texture(tex, pos).r * 255.0 / 2.0 + 0.25);
+    float k = floor(texture(classes, pos).r * 255.0 + 0.5);
+    color = vec4((tint(k) + half_byte) / 255.0, 1);
+}
@@ -0,0 +1,32 @@
+#version 140
+
+uniform vec3 window;
+// How many pixels right the line is on each row, over 255.
+uniform sampler1D levels;
+// First and last rows showing the bytes on screen.
+uniform vec2 visible;
+
+in vec2 pos;
+out vec4 color;
+
+float level(in float row) {
+    return floor(texture(levels, (row + 0.5) / window.y).r * 255.0 + 0.5);
+}
+
+// Entropy down the rows, growing to the right, filled in below the line.
+// The line goes across from where the row above left it.
+void main() {
+    float row = floor(pos.y * window.y);
+    float column = floor(pos.x * window.x);
+    float here = level(row);
+    float previous = row == 0.0 ? here : level(row - 1.0);
+    if (column >= min(previous, here) && column <= max(previous, here)) {
+        color = vec4(0.0, 192.0/255.0, 1.0, 1.0);
+    } else if (column < min(previous, here)) {
+        color = vec4(0.0, 64.0/255.0, 96.0/255.0, 1.0);
+    } else if (row >= visible.x && row <= visible.y) {
+        color = vec4(48.0/255.0, 48.0/255.0, 48.0/255.0, 1.0);
+    } else {
+        color = vec4(0.0, 0.0, 0.0, 1.0);
+    }
+}
@@ -0,0 +1,12 @@
+#version 140
+
+uniform vec3 window;
+uniform sampler2D tex;
+
+in vec2 pos;
+out vec4 color;
+
+// Where no memory is mapped or no block written, there is no byte to colour.
+void main() {
+    color = vec4(0, 64.0 / 255.0, 64.0 / 255.0, 1);
+}
@@ -0,0 +1,13 @@
+#version 140
+
+uniform vec3 window;
+uniform sampler2D tex;
+
+in vec2 pos;
+out vec4 color;
+
+// Only drawn where a search hit is: green to cyan with the byte value.
+void main() {
+    float c = texture(tex, pos).r;
+    color = vec4(0, 1, c, 1);
+}
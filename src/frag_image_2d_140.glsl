@@ -0,0 +1,11 @@
+#version 140
+
+uniform vec3 window;
+uniform sampler2D image;
+
+in vec2 pos;
+out vec4 color;
+
+void main() {
+    color = vec4(texture(image, pos).rgb, 1);
+}
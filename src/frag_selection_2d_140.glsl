@@ -0,0 +1,13 @@
+#version 140
+
+uniform vec3 window;
+uniform sampler2D tex;
+
+in vec2 pos;
+out vec4 color;
+
+// Only drawn where the selection is: blue to white with the byte value.
+void main() {
+    float c = texture(tex, pos).r;
+    color = vec4(c, c, 1, 1);
+}
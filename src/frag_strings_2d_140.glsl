@@ -0,0 +1,13 @@
+#version 140
+
+uniform vec3 window;
+uniform sampler2D tex;
+
+in vec2 pos;
+out vec4 color;
+
+// Only drawn where a string is: orange to yellow with the byte value.
+void main() {
+    float c = texture(tex, pos).r;
+    color = vec4(1, c, 0, 1);
+}
@@ -0,0 +1,18 @@
+#version 140
+
+uniform vec3 window;
+uniform sampler2D tex;
+uniform sampler2D tex_b;
+
+in vec2 pos;
+out vec4 color;
+
+void main() {
+    float a = texture(tex, pos).r;
+    float b = texture(tex_b, pos).r;
+    if (a == b) {
+        color = vec4(a / 4, a / 4, a / 4, 1);
+    } else {
+        color = vec4(1, abs(a - b), 0, 1);
+    }
+}
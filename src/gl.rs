@@ -0,0 +1,373 @@
+//! The OpenGL renderer: a window drawn into with the GLSL 1.40 shaders of `src/`,
+//! or for machines with OpenGL but no display, a headless context (OSMesa on Linux)
+//! drawing into a framebuffer object with the very same shaders.
+
+use std::collections::HashMap;
+
+use glium;
+use glium::{DisplayBuild, Surface};
+use glium::backend::Facade;
+use glium::backend::glutin_backend::GlutinFacade;
+use glium::framebuffer::SimpleFrameBuffer;
+use glium::texture::{DepthTexture1d, DepthTexture2d, RawImage2d, Texture1d, Texture2d};
+use glium::uniforms::{MagnifySamplerFilter, MinifySamplerFilter};
+
+use entropy;
+use error::Error;
+use palette::{Mark, Mode};
+use raster::Image;
+use render::{Input, Layout, Renderer, Scene, SCROLL_ROWS};
+use super::{Vertex, point, points, bytes_to_texture2d, detail_map, make_depth1d_texture, mode_program};
+
+pub struct Gl {
+    display: GlutinFacade,
+    width: u32,
+    height: u32,
+    texture_detail: Texture1d,
+    /// Built for this mode, or for xor when `None`.
+    program: Option<(Option<Mode>, glium::Program)>,
+    /// One point per pixel of a pane this many pixels wide and high.
+    points: Option<((u32, u32), glium::VertexBuffer<Vertex>)>,
+    /// One per pane, or both inputs for the xor pane.
+    textures: Vec<DepthTexture2d>,
+    /// The class of the block of each byte of each pane, in `Mode::Class`.
+    classes: Vec<DepthTexture2d>,
+    panes: Vec<glium::Rect>,
+    dims: [f32; 3],
+    /// Points of the marked bytes of each pane, drawn in the order of the scene.
+    marks: Vec<(usize, Mark, glium::VertexBuffer<Vertex>)>,
+    mark_programs: HashMap<Mark, glium::Program>,
+    panels: Vec<(glium::Rect, Texture2d, glium::VertexBuffer<Vertex>)>,
+    image_program: Option<glium::Program>,
+    /// The entropy strip, its levels and the rows on screen.
+    graph: Option<(glium::Rect, DepthTexture1d, glium::VertexBuffer<Vertex>, [f32; 2])>,
+    entropy_program: Option<glium::Program>,
+    /// Drawn into instead of the window when headless.
+    offscreen: Option<Texture2d>,
+    /// Last known mouse position, from the top left.
+    cursor: (i32, i32),
+    /// Whether the left button is down.
+    held: bool,
+}
+
+impl Gl {
+    pub fn new(title: &str) -> Result<Gl, Error> {
+        let display = try!(glium::glutin::WindowBuilder::new()
+            .with_title(title)
+            .with_decorations(false)
+            .with_vsync()
+            .build_glium());
+        let (width, height) = display.get_context().get_framebuffer_dimensions();
+        Gl::with(display, width, height, None)
+    }
+
+    /// Draws `width`x`height` pixels without a window, only to be read back.
+    pub fn headless(width: u32, height: u32) -> Result<Gl, Error> {
+        if width == 0 || height == 0 {
+            return Err(Error::Layout(width, height));
+        }
+        let display = try!(glium::glutin::HeadlessRendererBuilder::new(width, height).build_glium());
+        let texture = try!(Texture2d::empty_with_format(&display,
+                                                        glium::texture::UncompressedFloatFormat::U8U8U8U8,
+                                                        glium::texture::MipmapsOption::NoMipmap,
+                                                        width, height));
+        Gl::with(display, width, height, Some(texture))
+    }
+
+    fn with(display: GlutinFacade, width: u32, height: u32, offscreen: Option<Texture2d>)
+            -> Result<Gl, Error> {
+        debug!("OpenGL version {:?}", display.get_opengl_version());
+        let texture_detail = try!(detail_map(&display));
+        Ok(Gl {
+            display: display,
+            width: width,
+            height: height,
+            texture_detail: texture_detail,
+            program: None,
+            points: None,
+            textures: vec![],
+            classes: vec![],
+            panes: vec![],
+            dims: [0f32; 3],
+            marks: vec![],
+            mark_programs: HashMap::new(),
+            panels: vec![],
+            image_program: None,
+            graph: None,
+            entropy_program: None,
+            offscreen: offscreen,
+            cursor: (0, 0),
+            held: false,
+        })
+    }
+
+    fn draw_panes<S: Surface>(&self, target: &mut S) -> Result<(), Error> {
+        let (program, vertex_buffer) = match (&self.program, &self.points) {
+            (&Some((_, ref program)), &Some((_, ref vertex_buffer))) => (program, vertex_buffer),
+            _ => return Ok(()),
+        };
+        let indices = glium::index::NoIndices(glium::index::PrimitiveType::Points);
+        let last = self.textures.len() - 1;
+        for (i, pane) in self.panes.iter().enumerate() {
+            let params = glium::DrawParameters {
+                viewport: Some(*pane),
+                .. Default::default()
+            };
+            let uniforms = uniform! {
+                window: self.dims,
+                tex: &self.textures[i],
+                tex_b: &self.textures[last],
+                tex_detail: &self.texture_detail,
+                classes: self.classes.get(i).unwrap_or(&self.textures[i]),
+            };
+            try!(target.draw(vertex_buffer, &indices, program, &uniforms, &params));
+        }
+
+        for &(i, mark, ref vertex_buffer) in &self.marks {
+            let params = glium::DrawParameters {
+                viewport: Some(self.panes[i]),
+                .. Default::default()
+            };
+            let uniforms = uniform! {
+                window: self.dims,
+                tex: &self.textures[i],
+            };
+            try!(target.draw(vertex_buffer, &indices, &self.mark_programs[&mark], &uniforms, &params));
+        }
+
+        if let Some(ref program) = self.image_program {
+            for &(rect, ref texture, ref vertex_buffer) in &self.panels {
+                let params = glium::DrawParameters {
+                    viewport: Some(rect),
+                    .. Default::default()
+                };
+                let uniforms = uniform! {
+                    window: [rect.width as f32, rect.height as f32, 1f32],
+                    image: texture.sampled().magnify_filter(MagnifySamplerFilter::Nearest),
+                };
+                try!(target.draw(vertex_buffer, &indices, program, &uniforms, &params));
+            }
+        }
+
+        if let (Some(ref program), &Some((rect, ref texture, ref vertex_buffer, visible))) =
+            (self.entropy_program.as_ref(), &self.graph) {
+            let params = glium::DrawParameters {
+                viewport: Some(rect),
+                .. Default::default()
+            };
+            let uniforms = uniform! {
+                window: [rect.width as f32, rect.height as f32, 1f32],
+                levels: texture.sampled()
+                    .magnify_filter(MagnifySamplerFilter::Nearest)
+                    .minify_filter(MinifySamplerFilter::Nearest),
+                visible: visible,
+            };
+            try!(target.draw(vertex_buffer, &indices, program, &uniforms, &params));
+        }
+        Ok(())
+    }
+}
+
+impl Renderer for Gl {
+    fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    fn load(&mut self, scene: &Scene) -> Result<(), Error> {
+        let height = self.height;
+        let row_len = scene.row_len();
+        let pane_height = scene.pane_height();
+
+        let key = if scene.layout == Layout::Xor { None } else { Some(scene.mode) };
+        if self.program.as_ref().map(|p| p.0) != Some(key) {
+            let program = try!(match key {
+                Some(mode) => mode_program(&self.display, mode),
+                None => program(&self.display, include_str!("frag_xor_2d_140.glsl")),
+            });
+            self.program = Some((key, program));
+        }
+        if self.points.as_ref().map(|p| p.0) != Some((row_len, pane_height)) {
+            let vertex_buffer = try!(glium::VertexBuffer::new(&self.display, &points(row_len, pane_height)));
+            self.points = Some(((row_len, pane_height), vertex_buffer));
+        }
+
+        let mut textures = vec![];
+        for buffer in &scene.buffers {
+            textures.push(try!(bytes_to_texture2d(&self.display, row_len, pane_height, buffer)));
+        }
+        self.textures = textures;
+
+        let mut classes = vec![];
+        for buffer_classes in &scene.classes {
+            let indices: Vec<u8> = buffer_classes.iter().map(|&class| class as u8).collect();
+            classes.push(try!(bytes_to_texture2d(&self.display, row_len, pane_height, &indices)));
+        }
+        self.classes = classes;
+
+        // Side by side, each pane gets half of the window with a one pixel gap between them,
+        // and in a grid a cell, its label under it.
+        self.panes = match scene.layout {
+            Layout::SideBySide | Layout::Grid{..} => (0..scene.buffers.len()).map(|i| {
+                let bottom = height - scene.pane_top(i) - pane_height;
+                glium::Rect{left: scene.pane_left(i), bottom: bottom, width: row_len, height: pane_height}
+            }).collect(),
+            Layout::Single | Layout::Xor =>
+                vec![glium::Rect{left: 0, bottom: 0, width: row_len, height: height}],
+        };
+        self.dims = [row_len as f32, pane_height as f32, 1f32];
+
+        self.marks.clear();
+        for marks in &scene.marks {
+            // Texture rows go down from the top, `points` rows up from the bottom.
+            let mut shape = vec![];
+            for &(start, end) in &marks.ranges {
+                for j in start..end {
+                    let (x, y) = (j as u32 % row_len, j as u32 / row_len);
+                    if y < pane_height {
+                        shape.push(point(x, pane_height - 1 - y, row_len, pane_height));
+                    }
+                }
+            }
+            if shape.is_empty() {
+                continue;
+            }
+            if !self.mark_programs.contains_key(&marks.mark) {
+                let fragment = match marks.mark {
+                    Mark::String => include_str!("frag_strings_2d_140.glsl"),
+                    Mark::Hit => include_str!("frag_hits_2d_140.glsl"),
+                    Mark::Selection => include_str!("frag_selection_2d_140.glsl"),
+                    Mark::Bookmark => include_str!("frag_bookmarks_2d_140.glsl"),
+                    Mark::Gap => include_str!("frag_gaps_2d_140.glsl"),
+                };
+                let program = try!(program(&self.display, fragment));
+                self.mark_programs.insert(marks.mark, program);
+            }
+            let vertex_buffer = try!(glium::VertexBuffer::new(&self.display, &shape));
+            self.marks.push((marks.buffer, marks.mark, vertex_buffer));
+        }
+
+        self.panels.clear();
+        for panel in &scene.panels {
+            let (w, h) = (panel.image.width, panel.image.height);
+            let rect = glium::Rect{left: panel.left, bottom: height.saturating_sub(panel.top + h), width: w, height: h};
+            // Rows from the top, as `bytes_to_texture2d` lays them out.
+            let raw = RawImage2d::from_raw_rgb(panel.image.rgb(), (w, h));
+            let texture = try!(Texture2d::with_format(&self.display, raw,
+                                                      glium::texture::UncompressedFloatFormat::U8U8U8,
+                                                      glium::texture::MipmapsOption::NoMipmap));
+            let vertex_buffer = try!(glium::VertexBuffer::new(&self.display, &points(w, h)));
+            self.panels.push((rect, texture, vertex_buffer));
+        }
+        if !self.panels.is_empty() && self.image_program.is_none() {
+            self.image_program = Some(try!(program(&self.display, include_str!("frag_image_2d_140.glsl"))));
+        }
+
+        self.graph = None;
+        if let Some(ref graph) = scene.graph {
+            let (w, h) = (entropy::WIDTH, graph.levels.len() as u32);
+            let rect = glium::Rect{left: graph.left, bottom: height - h, width: w, height: h};
+            // Whole numbers of pixels, over 255 so that they survive the depth format.
+            let levels = graph.levels.iter().map(|&level| level as f32 / 255f32).collect();
+            let texture = try!(make_depth1d_texture(&self.display, levels));
+            let vertex_buffer = try!(glium::VertexBuffer::new(&self.display, &points(w, h)));
+            let visible = [graph.visible.0 as f32, graph.visible.1 as f32];
+            self.graph = Some((rect, texture, vertex_buffer, visible));
+            if self.entropy_program.is_none() {
+                self.entropy_program = Some(try!(program(&self.display, include_str!("frag_entropy_2d_140.glsl"))));
+            }
+        }
+        Ok(())
+    }
+
+    fn draw(&mut self) -> Result<(), Error> {
+        if let Some(ref texture) = self.offscreen {
+            let mut target = try!(SimpleFrameBuffer::new(&self.display, texture));
+            target.clear_color(0.0, 0.0, 0.0, 1.0);
+            return self.draw_panes(&mut target);
+        }
+
+        let mut target = self.display.draw();
+        target.clear_color(0.0, 0.0, 0.0, 1.0);
+        let drawn = self.draw_panes(&mut target);
+        // A frame has to be finished even when drawing into it failed.
+        let finished = target.finish();
+        try!(drawn);
+        try!(finished);
+        Ok(())
+    }
+
+    fn input(&mut self) -> Vec<Input> {
+        if self.offscreen.is_some() {
+            return vec![Input::Quit];
+        }
+        let mut inputs = vec![];
+        for ev in self.display.poll_events() {
+            use glium::glutin::{Event, ElementState, MouseButton, MouseScrollDelta, VirtualKeyCode};
+            match ev {
+                Event::Closed => inputs.push(Input::Quit),
+                Event::KeyboardInput(ElementState::Released, _, Some(VirtualKeyCode::Escape)) =>
+                    inputs.push(Input::Escape),
+                Event::KeyboardInput(ElementState::Pressed, _, Some(key)) => match key {
+                    VirtualKeyCode::Up => inputs.push(Input::Scroll(-SCROLL_ROWS)),
+                    VirtualKeyCode::Down => inputs.push(Input::Scroll(SCROLL_ROWS)),
+                    VirtualKeyCode::PageUp => inputs.push(Input::Page(-1)),
+                    VirtualKeyCode::PageDown => inputs.push(Input::Page(1)),
+                    VirtualKeyCode::Home => inputs.push(Input::Home),
+                    VirtualKeyCode::LBracket => inputs.push(Input::Select(-1)),
+                    VirtualKeyCode::RBracket => inputs.push(Input::Select(1)),
+                    VirtualKeyCode::Back => inputs.push(Input::Backspace),
+                    VirtualKeyCode::Return => inputs.push(Input::Enter),
+                    _ => ()
+                },
+                Event::ReceivedCharacter(c) if !c.is_control() => inputs.push(Input::Char(c)),
+                Event::MouseMoved(x, y) => {
+                    self.cursor = (x, y);
+                    if self.held && x >= 0 && y >= 0 {
+                        inputs.push(Input::Drag(x as u32, y as u32));
+                    }
+                }
+                Event::MouseInput(ElementState::Pressed, MouseButton::Left) if self.cursor.0 >= 0 && self.cursor.1 >= 0 => {
+                    self.held = true;
+                    inputs.push(Input::Click(self.cursor.0 as u32, self.cursor.1 as u32));
+                }
+                Event::MouseInput(ElementState::Released, MouseButton::Left) if self.held => {
+                    self.held = false;
+                    inputs.push(Input::Release);
+                }
+                Event::MouseWheel(MouseScrollDelta::LineDelta(_, y), _) =>
+                    inputs.push(Input::Scroll(-(y * SCROLL_ROWS as f32) as i64)),
+                Event::MouseWheel(MouseScrollDelta::PixelDelta(_, y), _) =>
+                    inputs.push(Input::Scroll(-y as i64)),
+                _ => ()
+            }
+        }
+        inputs
+    }
+
+    fn frame(&self) -> Result<Image, Error> {
+        let raw: RawImage2d<u8> = match self.offscreen {
+            Some(ref texture) => texture.read(),
+            None => self.display.read_front_buffer(),
+        };
+        let mut image = Image::new(raw.width, raw.height);
+        // OpenGL rows go from the bottom up, in textures as on screen.
+        for (i, c) in raw.data.chunks(4).enumerate() {
+            let (x, y) = (i as u32 % raw.width, i as u32 / raw.width);
+            image.set(x, raw.height - 1 - y, [c[0], c[1], c[2]]);
+        }
+        Ok(image)
+    }
+}
+
+/// Draws `points` with the `fragment` shader.
+fn program<F: ?Sized>(display: &F, fragment: &str) -> Result<glium::Program, Error>
+    where F: Facade + ::std::marker::Sized
+{
+    program!(display,
+             140 => {
+                 point_size: true,
+                 vertex: include_str!("vert_2d_140.glsl"),
+                 fragment: fragment,
+             })
+        .map_err(|e| Error::from_program(e, *display.get_context().get_opengl_version()))
+}
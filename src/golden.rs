@@ -0,0 +1,353 @@
+//! Golden image tests: each colour mode in each layout, rasterized on the CPU from
+//! `tests/fixtures/` and compared pixel for pixel with the pictures in `tests/golden/`.
+//!
+//! When a picture changes on purpose, `MINRS_BLESS=1 cargo test` rewrites the golden
+//! pictures so the change shows up for review. Otherwise each mismatch leaves in
+//! `target/golden/` what was rendered, `<name>.png`, and `<name>.diff.png` where the
+//! differing pixels are red over a dimmed copy of the golden picture.
+
+use std::env;
+use std::fs::{self, File};
+use std::io;
+use std::path::PathBuf;
+
+use png;
+
+use archive;
+use bits::Order;
+use bookmarks::{Bookmark, Project};
+use cpu::Cpu;
+use fixtures::{self, root};
+use palette::Mode;
+use pixels::Pixels;
+use raster::{self, Image};
+use render::{self, Layout, View};
+use samples::Format;
+use source::Source;
+
+const MODES: [Mode; 3] = [Mode::Bw, Mode::Four, Mode::Detail];
+
+fn load_png(path: &PathBuf) -> io::Result<Image> {
+    let decoder = png::Decoder::new(try!(File::open(path)));
+    let (info, mut reader) = try!(decoder.read_info());
+    if info.color_type != png::ColorType::RGB || info.bit_depth != png::BitDepth::Eight {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "not 8-bit RGB"));
+    }
+    let mut rgb = vec![0; info.buffer_size()];
+    try!(reader.next_frame(&mut rgb));
+    let mut image = Image::new(info.width, info.height);
+    for (pixel, c) in image.pixels.iter_mut().zip(rgb.chunks(3)) {
+        *pixel = [c[0], c[1], c[2]];
+    }
+    Ok(image)
+}
+
+fn diff_image(expected: &Image, mismatches: &[(u32, u32)]) -> Image {
+    let mut diff = Image::new(expected.width, expected.height);
+    for (pixel, c) in diff.pixels.iter_mut().zip(&expected.pixels) {
+        *pixel = [c[0] / 4, c[1] / 4, c[2] / 4];
+    }
+    for &(x, y) in mismatches {
+        diff.set(x, y, [255, 0, 0]);
+    }
+    diff
+}
+
+/// Compares `actual` with `tests/golden/<name>.png`, or replaces it when blessing.
+fn check(name: &str, actual: &Image) -> Result<(), String> {
+    let golden = root().join("tests/golden").join(format!("{}.png", name));
+    if env::var_os("MINRS_BLESS").is_some() {
+        return actual.save_png(&golden).map_err(|e| format!("{}: cannot write {:?}: {}", name, golden, e));
+    }
+
+    let expected = try!(load_png(&golden).map_err(|e| {
+        format!("{}: cannot read {:?}: {} (MINRS_BLESS=1 creates it)", name, golden, e)
+    }));
+    if (expected.width, expected.height) != (actual.width, actual.height) {
+        return Err(format!("{}: rendered {}x{} but golden is {}x{}", name,
+                           actual.width, actual.height, expected.width, expected.height));
+    }
+    let found = raster::mismatches(&expected, actual);
+    if found.is_empty() {
+        return Ok(());
+    }
+
+    let out = root().join("target/golden");
+    fs::create_dir_all(&out).unwrap();
+    actual.save_png(&out.join(format!("{}.png", name))).unwrap();
+    diff_image(&expected, &found).save_png(&out.join(format!("{}.diff.png", name))).unwrap();
+    Err(format!("{}: {} of {} pixels differ, first at {:?}, see {:?}", name, found.len(),
+                expected.pixels.len(), found[0], out.join(format!("{}.diff.png", name))))
+}
+
+fn assert_all(results: Vec<Result<(), String>>) {
+    let failures: Vec<String> = results.into_iter().filter_map(|r| r.err()).collect();
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
+
+fn name(fixture: &str, layout: &str, mode: Mode) -> String {
+    format!("{}-{}-{:?}", fixture, layout, mode).to_lowercase()
+}
+
+#[test]
+fn single_pane() {
+    let text = fixtures::read("text.bin");
+    let random = fixtures::read("random.bin");
+    assert_all(MODES.iter().flat_map(|&mode| vec![
+        check(&name("text", "single", mode), &raster::bytes(mode, 64, 32, &text)),
+        check(&name("random", "single", mode), &raster::bytes(mode, 128, 96, &random)),
+    ]).collect());
+}
+
+#[test]
+fn overview() {
+    let text = fixtures::read("text.bin");
+    let random = fixtures::read("random.bin");
+    assert_all(MODES.iter().flat_map(|&mode| vec![
+        check(&name("text", "overview", mode),
+              &raster::overview(mode, 16, 16, &text[..], text.len() as u64).unwrap()),
+        check(&name("random", "overview", mode),
+              &raster::overview(mode, 32, 32, &random[..], random.len() as u64).unwrap()),
+    ]).collect());
+}
+
+#[test]
+fn side_by_side() {
+    let a = fixtures::read("text.bin");
+    let b = fixtures::read("text-patched.bin");
+    assert_all(MODES.iter().map(|&mode| {
+        check(&name("text", "side-by-side", mode), &raster::side_by_side(mode, 129, 32, &a, &b))
+    }).collect());
+}
+
+#[test]
+fn xor() {
+    let a = fixtures::read("text.bin");
+    let b = fixtures::read("text-patched.bin");
+    assert_all(vec![check("text-xor", &raster::xor(64, 32, &a, &b))]);
+}
+
+/// The first frame of the render loop on the CPU, saved as `png` and checked against
+/// the scene it drew.
+fn first_frame<'a>(view: &mut View<'a>, png: &'a PathBuf, width: u32, height: u32) -> Image {
+    view.png = Some(png.to_str().unwrap());
+    view.check = true;
+    render::run(&mut Cpu::offscreen(width, height), view).unwrap();
+    load_png(png).unwrap()
+}
+
+#[test]
+fn render_loop() {
+    let text = fixtures::path("text.bin");
+    let png = fixtures::out("render-loop.png");
+    let mut view = View::new(vec![text.to_str().unwrap()], Layout::Single, Mode::Four).unwrap();
+    assert_all(vec![check("text-single-four", &first_frame(&mut view, &png, 64, 32))]);
+}
+
+#[test]
+fn strings() {
+    let path = fixtures::path("strings.bin");
+    let png = fixtures::out("strings.png");
+    let mut view = View::new(vec![path.to_str().unwrap()], Layout::Single, Mode::Four).unwrap();
+    view.find_strings(4).unwrap();
+    assert_all(vec![check("strings-single-four", &first_frame(&mut view, &png, 360, 48))]);
+}
+
+#[test]
+fn search() {
+    let path = fixtures::path("strings.bin");
+    let png = fixtures::out("search.png");
+    let mut view = View::new(vec![path.to_str().unwrap()], Layout::Single, Mode::Four).unwrap();
+    view.search("hex:90 ?? 90").unwrap();
+    assert_all(vec![check("search-single-four", &first_frame(&mut view, &png, 160, 24))]);
+}
+
+#[test]
+fn bookmarks() {
+    // A copy of a fixture, so that its sidecar is written under target/.
+    let path = fixtures::write("bookmarks.bin", &fixtures::read("strings.bin"));
+    let input = path.to_str().unwrap();
+    let _ = fs::remove_file(format!("{}.minrs.json", input));
+
+    let mut project = Project::load(input).unwrap();
+    project.add(Bookmark{offset: 102, len: 38, name: "System32".to_owned()});
+    project.add(Bookmark{offset: 37, len: 14, name: "GetProcAddress".to_owned()});
+    project.add(Bookmark{offset: 400, len: 1, name: "one byte".to_owned()});
+    project.save().unwrap();
+
+    let png = fixtures::out("bookmarks.png");
+    let mut view = View::new(vec![input], Layout::Single, Mode::Four).unwrap();
+    assert_all(vec![check("bookmarks-single-four", &first_frame(&mut view, &png, 32, 16))]);
+}
+
+#[test]
+fn minimap() {
+    let path = fixtures::path("random.bin");
+    let png = fixtures::out("minimap.png");
+    let mut view = View::new(vec![path.to_str().unwrap()], Layout::Single, Mode::Detail).unwrap();
+    view.minimap = true;
+    assert_all(vec![check("minimap-single-detail", &first_frame(&mut view, &png, 160, 48))]);
+}
+
+#[test]
+fn entropy() {
+    // Text, then random bytes, then zeros: entropy in the middle, then high, then none.
+    let mut bytes = fixtures::read("text.bin");
+    bytes.extend_from_slice(&fixtures::read("random.bin")[..4096]);
+    bytes.extend_from_slice(&[0; 2048]);
+    let path = fixtures::write("entropy.bin", &bytes);
+    let input = path.to_str().unwrap();
+
+    let png = fixtures::out("entropy.png");
+    let mut view = View::new(vec![input], Layout::Single, Mode::Four).unwrap();
+    view.minimap = true;
+    view.find_entropy(256).unwrap();
+    assert_all(vec![check("entropy-single-four", &first_frame(&mut view, &png, 288, 32))]);
+}
+
+#[test]
+fn classes() {
+    let random = fixtures::read("random.bin");
+    // Random bytes with the low bit cleared in every other one: dense but uneven.
+    let skewed: Vec<u8> = random[4096..6144].iter().enumerate()
+        .map(|(i, &b)| if i % 2 == 0 { b & 0xfe } else { b })
+        .collect();
+    // Little-endian counters, as in tables of offsets.
+    let counters: Vec<u8> = (0..512u32).flat_map(|i| vec![i as u8, (i >> 8) as u8, 0, 0]).collect();
+    // The text fixture ends in binary, which makes its second block code.
+    let mut bytes = fixtures::read("text.bin");
+    bytes.extend_from_slice(&random[..4096]);
+    bytes.extend_from_slice(&skewed);
+    bytes.extend_from_slice(&counters);
+    bytes.extend_from_slice(&[0; 2048]);
+    let path = fixtures::write("classes.bin", &bytes);
+    let png = fixtures::out("classes.png");
+    let mut view = View::new(vec![path.to_str().unwrap()], Layout::Single, Mode::Class).unwrap();
+    assert_all(vec![check("classes-single-class", &first_frame(&mut view, &png, 128, 96))]);
+}
+
+#[test]
+fn stride() {
+    let random = fixtures::read("random.bin");
+    // Records of 37 bytes: a tag, a little-endian counter and random bytes.
+    let bytes: Vec<u8> = random.chunks(32).take(64).enumerate()
+        .flat_map(|(i, chunk)| {
+            let mut record = b"REC".to_vec();
+            record.extend_from_slice(&[i as u8, (i >> 8) as u8]);
+            record.extend_from_slice(chunk);
+            record
+        })
+        .collect();
+    let path = fixtures::write("stride.bin", &bytes);
+    let png = fixtures::out("stride.png");
+    let mut view = View::new(vec![path.to_str().unwrap()], Layout::Single, Mode::Four).unwrap();
+    view.auto_stride = true;
+    assert_all(vec![check("stride-single-four", &first_frame(&mut view, &png, 128, 64))]);
+}
+
+#[test]
+fn samples() {
+    // A 16 bit ramp over a few thousand values, with the odd spike to the top.
+    let bytes: Vec<u8> = (0..4096u32)
+        .map(|i| if i % 97 == 0 { 0xffff } else { 1000 + i * 3 })
+        .flat_map(|v| vec![v as u8, (v >> 8) as u8])
+        .collect();
+    let path = fixtures::write("samples.bin", &bytes);
+    let png = fixtures::out("samples.png");
+    let mut view = View::new(vec![path.to_str().unwrap()], Layout::Single, Mode::Bw).unwrap();
+    view.decode(Format::U16le).unwrap();
+    assert_all(vec![check("samples-single-bw", &first_frame(&mut view, &png, 64, 64))]);
+}
+
+#[test]
+fn pixels() {
+    // An RGB picture 48 pixels wide: red across, green down, blue in a square.
+    let bytes: Vec<u8> = (0..48 * 40u32)
+        .flat_map(|i| {
+            let (x, y) = (i % 48, i / 48);
+            let blue = if 16 <= x && x < 32 && 12 <= y && y < 28 { 255 } else { 0 };
+            vec![(x * 5) as u8, (y * 6) as u8, blue]
+        })
+        .collect();
+    let path = fixtures::write("pixels.bin", &bytes);
+    let png = fixtures::out("pixels.png");
+    let mut view = View::new(vec![path.to_str().unwrap()], Layout::Single, Mode::Four).unwrap();
+    view.pixels = Some(Pixels::Rgb);
+    view.stride = Some(48);
+    assert_all(vec![check("pixels-single-rgb", &first_frame(&mut view, &png, 96, 48))]);
+}
+
+#[test]
+fn bits() {
+    // Rows of 60 bits are cut down to 56, 7 bytes, the bits of each hit highlighted.
+    let path = fixtures::path("strings.bin");
+    let png = fixtures::out("bits.png");
+    let mut view = View::new(vec![path.to_str().unwrap()], Layout::Single, Mode::Four).unwrap();
+    view.bits = Some(Order::Msb);
+    view.stride = Some(60);
+    view.search("lib").unwrap();
+    assert_all(vec![check("bits-single-four", &first_frame(&mut view, &png, 64, 64))]);
+}
+
+#[test]
+fn decompress() {
+    let bytes = fixtures::read("strings.bin");
+    for (extension, _, data) in fixtures::compressed(&bytes) {
+        // Decompressed, the input looks just as it does uncompressed.
+        let path = fixtures::write(&format!("strings.bin.{}", extension), &data);
+        let png = fixtures::out(&format!("decompress-{}.png", extension));
+        let mut view = View::new(vec![path.to_str().unwrap()], Layout::Single, Mode::Four).unwrap();
+        view.decompress().unwrap();
+        view.search("hex:90 ?? 90").unwrap();
+        assert_all(vec![check("search-single-four", &first_frame(&mut view, &png, 160, 24))]);
+    }
+}
+
+#[test]
+fn archives() {
+    let (text, strings, random) = (fixtures::read("text.bin"), fixtures::read("strings.bin"), fixtures::read("random.bin"));
+    let long = format!("{}/strings.bin", vec!["deep"; 30].concat());
+    let archives = vec![
+        ("tar", fixtures::tar(&[("text.bin", &text), (&long, &strings), ("random.bin", &random)])),
+        ("zip", fixtures::zip(&[("text.bin", &text, false), (&long, &strings, true), ("random.bin", &random, true)])),
+    ];
+    for (extension, data) in archives {
+        let path = fixtures::write(&format!("fixtures.{}", extension), &data);
+        let source = Source::raw(path.to_str().unwrap());
+        let members = archive::members(&source).unwrap().unwrap();
+
+        // A member looks just as the file it was made from.
+        let png = fixtures::out(&format!("archive-{}.png", extension));
+        let mut view = View::new(vec![source.path], Layout::Single, Mode::Four).unwrap();
+        view.open_member(members[0].clone()).unwrap();
+        assert_all(vec![check("text-single-four", &first_frame(&mut view, &png, 64, 32))]);
+
+        let png = fixtures::out(&format!("mosaic-{}.png", extension));
+        let mut view = View::new(vec![source.path], Layout::Single, Mode::Four).unwrap();
+        view.png = Some(png.to_str().unwrap());
+        view.check = true;
+        assert_eq!(render::mosaic(&mut Cpu::offscreen(160, 96), &view, &members).unwrap(), None);
+        assert_all(vec![check("mosaic-four", &load_png(&png).unwrap())]);
+    }
+}
+
+/// Where the filesystem keeps track of holes, they are drawn apart from zeros.
+#[test]
+#[cfg(target_os = "linux")]
+fn sparse_file() {
+    let (path, _) = fixtures::sparse("sparse.img");
+    let png = fixtures::out("sparse.png");
+    let mut view = View::new(vec![path.to_str().unwrap()], Layout::Single, Mode::Four).unwrap();
+    assert_all(vec![check("sparse-single-four", &first_frame(&mut view, &png, 128, 64))]);
+}
+
+#[test]
+fn grid() {
+    let files: Vec<PathBuf> = ["text.bin", "text-patched.bin", "random.bin"].iter()
+        .map(|name| fixtures::path(name))
+        .collect();
+    let png = fixtures::out("grid.png");
+    let paths = files.iter().map(|file| file.to_str().unwrap()).collect();
+    let mut view = View::new(paths, Layout::grid(files.len()), Mode::Four).unwrap();
+    assert_all(vec![check("grid-four", &first_frame(&mut view, &png, 129, 96))]);
+}
@@ -1,225 +1,448 @@
 #[macro_use]
 extern crate glium;
 
+extern crate bzip2;
+extern crate flate2;
 extern crate rustc_serialize;
 extern crate docopt;
+extern crate libc;
+extern crate num_cpus;
+extern crate png;
+extern crate regex;
+extern crate term_size;
+extern crate xz2;
+extern crate zstd;
+
+use std::cmp;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use glium::backend::Facade;
 
+/// Set by --verbose.
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+
+/// Says what is going on, on standard error and only with --verbose, so that standard
+/// output is left to what was asked for and to frames drawn in the terminal.
+macro_rules! debug {
+    ($($arg:tt)*) => (
+        if ::VERBOSE.load(::std::sync::atomic::Ordering::Relaxed) {
+            use std::io::Write;
+            let _ = writeln!(::std::io::stderr(), $($arg)*);
+        }
+    )
+}
+
+mod archive;
+mod bits;
+mod bookmarks;
+mod carve;
+mod classify;
+mod cpu;
+mod diff;
+mod entropy;
+mod error;
+#[cfg(test)]
+mod fixtures;
+mod font;
+mod gl;
+mod minimap;
+mod mosaic;
+#[cfg(test)]
+mod golden;
+mod palette;
+mod pixels;
+mod process;
+mod raster;
+mod render;
+mod samples;
+mod search;
+mod similarity;
+mod sixel;
+mod source;
+mod sparse;
+mod strings;
+mod stride;
+mod term;
+mod thumbs;
+
+use bits::Order;
+use cpu::Cpu;
+use error::Error;
+use palette::Mode;
+use pixels::Pixels;
+use process::Process;
+use render::{Layout, Renderer, View};
+use samples::Format;
+use term::Graphics;
+
 const NAME: &'static str = "minrs";
 
 const USAGE: &'static str = r#"
 I kept dreaming of a world I thought I'd never see
 
 Usage:
-  minrs <file>
+  minrs diff [options] <a> <b>
+  minrs thumbs [options] <dir> --out=<out>
+  minrs bookmark [options] <file> [<at> <name>]
+  minrs entropy [options] <file> --csv=<csv>
+  minrs similarity [options] <a> <b>
+  minrs [options] <file>...
+  minrs [options] --pid=<pid>
   minrs (-h | --help)
   minrs --version
 
 Options:
-  -v, --verbose  Show debug info on stdout.
-  -h, --help     Show this screen.
-  --version      Show version.
+  --mode=<mode>    Colour mode: bw, four, detail, or class to tint each block by whether
+                   it looks like text, code, compressed or random data [default: detail].
+  --entry=<e>      Show the member of a ZIP or TAR archive with this name, or number
+                   in the list of them printed on standard output.
+  --mosaic         Show a thumbnail of each member of a ZIP or TAR archive, then the
+                   one clicked.
+  --pid=<pid>      Show the memory of a running process on Linux, each mapping in
+                   address order and labelled, with a gap where none is mapped.
+  --raw            Take gzip, xz, zstd and bzip2 inputs as they are rather than what they
+                   decompress to. Going far into a decompressed input is slow, as it is
+                   decompressed again from the start.
+  --format=<f>     Samples to draw one per pixel, scaled from the lowest to the highest
+                   in the input: u8, i8, u16le, u16be, i16le, i16be, u32le, u32be,
+                   i32le, i32be, f32le, f32be, f64le or f64be. u8 bytes are drawn as
+                   they are, unscaled [default: u8].
+  --bits=<order>   Draw each bit as a pixel, a set one as 0xff and a clear one as 0, with
+                   the msb or the lsb of each byte first. Rows are whole bytes.
+  --pixels=<p>     Decode the bytes as a picture as wide as the rows, which --stride sets:
+                   gray, rgb, bgr, rgba, bgra, rgb565 or yuv420, planar. Not with --xor.
+  --height=<n>     Rows of each frame of --pixels=yuv420, whose chroma planes follow its
+                   rows; scrolling goes a frame at a time. 0 for as many as fit down
+                   [default: 0].
+  --term           Draw the first frame in the terminal instead of opening a window,
+                   then quit: there are no keys or mouse to move around with.
+  --graphics=<g>   How to draw in the terminal: blocks, sixel or kitty [default: blocks].
+  --cpu            Rasterize on the CPU, without OpenGL: in the terminal, or with --png.
+  --png=<file>     Save the first frame as a PNG file and quit, drawn without a
+                   window by headless OpenGL (OSMesa) unless --cpu is given.
+  --check          Fail unless the first frame matches the CPU rasterization pixel for pixel.
+  --xor            Show one pane highlighting the bytes that differ.
+  --no-minimap     Leave out the strip along the right edge that shows the whole input
+                   and frames what is on screen: click or drag in it to go there.
+                   Devices, processes and compressed inputs never have one.
+  --strings        Highlight printable ASCII and UTF-16LE strings and list them on the
+                   right: click one or press [ and ] to go to it.
+  --min-len=<n>    Fewest characters in a string [default: 4].
+  --search=<pat>   Highlight the bytes matching <pat> and start at the first hit: text,
+                   hex:4d5a??00 with ?? for any byte, or re:<regex> over bytes.
+                   In the window, / searches again, n and N go to the next and previous hit.
+  --entropy        Graph the entropy of each block of the input down the right edge,
+                   from 0 on the left to 8 bits per byte on the right.
+  --block=<n>      Bytes per block of --entropy, --mode=class, `minrs entropy` and
+                   `minrs similarity` [default: 1024].
+  --csv=<csv>      File to write the offset and entropy of each block to.
+  --stride=<n>     Bytes per row, to line fixed size records up in columns; 0 for as
+                   many as fit across [default: 0].
+  --auto-stride    Start with the record size that the first bytes repeat at.
+  --size=<px>      Width and height of thumbnails and of --png frames [default: 256].
+  --out=<out>      Directory to write thumbnails and their index.html to.
+  -v, --verbose    Show debug info on standard error.
+  -h, --help       Show this screen.
+  --version        Show version.
+
+Selecting:
+  In the window, click a byte or drag over a range of them, then press c to print
+  their offsets, x to hexdump them or w to write them to a new file. Escape unselects.
+  With several panes, the bytes are those of the file in the pane clicked.
+
+Strides:
+  In the window, s finds the record size that the bytes on screen repeat at and lays
+  them out that many to a row; + and - widen or narrow rows a byte at a time, 0 undoes.
+
+Similarity:
+  `minrs similarity` prints, as JSON on standard output, the Jensen-Shannon distances
+  between the byte histograms and between the byte pair distributions of <a> and <b>,
+  from 0 for the same to 1, and the correlation of the entropy of their blocks at the
+  same offsets, null when it can't be told.
+
+Several files:
+  Shown in a grid of panes, each named under its own, in the same mode. Scrolling,
+  jumps and strides move all of them together, so the same offsets line up. There is
+  no zoom: + and - widen or narrow the rows of every pane at once instead.
+
+Bookmarks:
+  Kept in <file>.minrs.json, or in <file>.<codec>.minrs.json for the bytes that it
+  decompresses to, such as x.gz.gzip.minrs.json, and outlined in magenta.
+  `minrs bookmark` lists them, or adds one named <name> at <at>: an offset or
+  <offset>+<len>, in decimal or 0x hex.
+  In the window, b bookmarks the selection and d deletes the bookmarks over it;
+  . and , go to the next and previous bookmark of the file in the pane last clicked.
+
+Exit status:
+  0  Success.
+  1  Invalid command line, search pattern or bookmark range.
+  2  The input can't be read or is empty.
+  3  The window, terminal or thumbnails are too small to show anything.
+  4  A shader failed to compile.
+  5  No usable OpenGL context.
+  6  The output can't be written.
+  7  The frame differs from the CPU rasterization (--check).
 "#;
 
 #[derive(Debug, RustcDecodable)]
 struct Args {
-    arg_file: String,
+    cmd_diff: bool,
+    cmd_thumbs: bool,
+    cmd_bookmark: bool,
+    cmd_entropy: bool,
+    cmd_similarity: bool,
+    arg_file: Vec<String>,
+    arg_a: String,
+    arg_b: String,
+    arg_dir: String,
+    arg_at: String,
+    arg_name: String,
+    flag_mode: Mode,
+    flag_pid: Option<u32>,
+    flag_raw: bool,
+    flag_entry: String,
+    flag_mosaic: bool,
+    flag_format: Format,
+    flag_pixels: Option<Pixels>,
+    flag_height: u32,
+    flag_bits: Option<Order>,
+    flag_term: bool,
+    flag_graphics: Graphics,
+    flag_cpu: bool,
+    flag_png: String,
+    flag_check: bool,
+    flag_xor: bool,
+    flag_no_minimap: bool,
+    flag_strings: bool,
+    flag_min_len: usize,
+    flag_search: String,
+    flag_entropy: bool,
+    flag_block: usize,
+    flag_csv: String,
+    flag_stride: u32,
+    flag_auto_stride: bool,
+    flag_size: u32,
+    flag_out: String,
     flag_verbose: bool,
     flag_version: bool,
 }
 
+#[derive(Copy, Clone)]
+struct Vertex {
+    position: [f32; 2],
+}
+
+implement_vertex!(Vertex, position);
+
+/// One point per pixel, placed at pixel centres so that each one samples exactly one texel.
+fn points(width: u32, height: u32) -> Vec<Vertex> {
+    let mut shape = Vec::with_capacity((width * height) as usize);
+    for y in 0..height {
+        for x in 0..width {
+            shape.push(point(x, y, width, height));
+        }
+    }
+    shape
+}
+
+/// The point of `points` at column `x`, `y` rows up from the bottom.
+fn point(x: u32, y: u32, width: u32, height: u32) -> Vertex {
+    let half_width = width as f32 / 2f32;
+    let half_height = height as f32 / 2f32;
+    let xx = (x as f32 + 0.5 - half_width) / half_width;
+    let yy = (y as f32 + 0.5 - half_height) / half_height;
+    Vertex{position: [xx, yy]}
+}
+
 fn main() {
     let args: Args = docopt::Docopt::new(USAGE)
         .and_then(|d| d.decode())
         .unwrap_or_else(|e| e.exit());
-    println!("{:?}", args);
+    VERBOSE.store(args.flag_verbose, Ordering::Relaxed);
+    debug!("{:?}", args);
+
+    if let Err(e) = run(args) {
+        let _ = writeln!(std::io::stderr(), "{}: {}", NAME, e);
+        std::process::exit(e.exit_code());
+    }
+}
 
+fn run(args: Args) -> Result<(), Error> {
     match args {
-        Args{flag_version: true, ..} =>
-            println!(env!("CARGO_PKG_VERSION")),
-        _ => {
-            // let fmeta = std::fs::metadata(args.arg_file.as_str()).map_err(|e| println!("{:?}", e));
-            // println!("{:?}", fmeta.is_file());
-
-            use glium::{DisplayBuild, Surface};
-            let display = glium::glutin::WindowBuilder::new()
-                .with_title(NAME)
-                .with_decorations(false)
-                .with_vsync()
-                .build_glium()
-                .unwrap();
-            let version = display.get_opengl_version();
-            println!("OpenGL version {:?}", version);
-            let (width, height) = display.get_context().get_framebuffer_dimensions();
-            println!("{:?}x{:?} = {:?}", width, height, width * height);
-
-            #[derive(Copy, Clone)]
-            struct Vertex {
-                position: [f32; 2],
-            }
-
-            implement_vertex!(Vertex, position);
-
-            let mut shape = vec![];
-            let half_width = width as f32 / 2f32;
-            let half_height = height as f32 / 2f32;
-            for y in 0..height {
-                for x in 0..width {
-                    let xx = (x as f32 - half_width) / half_width;
-                    let yy = (y as f32 - half_height) / half_height;
-                    shape.push(Vertex{position: [xx, yy]});
-                }
-            }
-            println!("shape size: {:?}", shape.len());
-
-            let vertex_buffer = glium::VertexBuffer::new(&display, &shape).unwrap();
-            // let texture = load_file_1d(&display, width, height, args.arg_file.as_str()).unwrap();
-            // let texture = file_to_texture(&display, width, height, args.arg_file.as_str()).unwrap();
-            // let texture = file_to_texture2d(&display, width, height, args.arg_file.as_str()).unwrap();
-            let texture = file_to_texture2d_(&display, width, height, args.arg_file.as_str()).unwrap();
-            let texture_detail = detail_map(&display).unwrap();
-            let indices = glium::index::NoIndices(glium::index::PrimitiveType::Points);
-            let dims = [texture.get_width() as f32,
-                        texture.get_height().unwrap_or(1) as f32,
-                        texture.get_depth().unwrap_or(1) as f32];
-            let uniforms = uniform! {
-                window: dims,
-                tex: &texture,
-                tex_detail: &texture_detail,
-            };
-
-            let program = program!(&display,
-                                   140 => {
-                                       point_size: true,
-                                       vertex: include_str!("vert_2d_140.glsl"),
-                                       fragment: "
-        #version 140
-
-        uniform sampler1D tex_detail;
-        uniform sampler2D tex;
-        uniform vec3 window;
-
-        in vec2 pos;
-        out vec4 color;
-
-        void main() {
-            // float idx = pos.y * window.y + 1*(pos.x * window.x) / (window.x * window.y);
-
-            // color = vec4(texture(tex, idx).rgb, 1);
-
-            // vec4 r = texture(tex, idx + 0);
-            // vec4 g = texture(tex, idx + 1);
-            // vec4 b = texture(tex, idx + 2);
-            // color = vec4(r.r, g.r, b.r, 1);
-
-            float pos1d = texture(tex, pos).r;
-            vec3 c = texture(tex_detail, pos1d).rgb;
-            color = vec4(c, 1);
-        }
-        ",
-                                   }).unwrap();
-
-            loop {
-                let mut target = display.draw();
-                target.clear_color(0.0, 0.0, 0.0, 1.0);
-                target.draw(&vertex_buffer, &indices, &program, &uniforms, &Default::default()).unwrap();
-                target.finish().unwrap();
-
-                for ev in display.poll_events() {
-                    use glium::glutin::VirtualKeyCode;
-                    match ev {
-                        glium::glutin::Event::Closed => return,
-                        glium::glutin::Event::KeyboardInput(glium::glutin::ElementState::Released, _, Some(VirtualKeyCode::Escape)) => return,
-                        _ => ()
-                    }
-                }
-            }
+        Args{flag_version: true, ..} => {
+            println!(env!("CARGO_PKG_VERSION"));
+            return Ok(());
         }
+        Args{cmd_thumbs: true, ..} =>
+            return thumbs::run(args.arg_dir.as_str(), args.flag_out.as_str(), args.flag_size, args.flag_mode),
+        Args{cmd_entropy: true, ..} =>
+            return entropy::run(args.arg_file[0].as_str(), cmp::max(1, args.flag_block), args.flag_csv.as_str(),
+                                args.flag_raw),
+        Args{cmd_similarity: true, ..} =>
+            return similarity::run(args.arg_a.as_str(), args.arg_b.as_str(), cmp::max(1, args.flag_block),
+                                   args.flag_raw),
+        Args{cmd_bookmark: true, ..} =>
+            return bookmarks::run(args.arg_file[0].as_str(), args.arg_at.as_str(), args.arg_name.as_str(),
+                                  args.flag_raw),
+        _ => ()
     }
-}
 
-#[derive(Debug)]
-enum LoadError {
-    Io(std::io::Error),
-    Gl(glium::texture::TextureCreationError),
-}
+    let process = match args.flag_pid {
+        Some(pid) => Some(try!(Process::new(pid))),
+        None => None,
+    };
+    let mut view = try!(match args {
+        Args{flag_pid: Some(_), ..} => View::process(process.as_ref().unwrap(), args.flag_mode),
+        Args{cmd_diff: true, flag_xor: true, ..} =>
+            View::new(vec![args.arg_a.as_str(), args.arg_b.as_str()], Layout::Xor, args.flag_mode),
+        Args{cmd_diff: true, ..} =>
+            View::new(vec![args.arg_a.as_str(), args.arg_b.as_str()], Layout::SideBySide, args.flag_mode),
+        Args{ref arg_file, ..} if arg_file.len() > 1 => {
+            let files = arg_file.iter().map(|file| file.as_str()).collect();
+            View::new(files, Layout::grid(arg_file.len()), args.flag_mode)
+        }
+        _ => View::new(vec![args.arg_file[0].as_str()], Layout::Single, args.flag_mode),
+    });
+    if !args.flag_raw {
+        try!(view.decompress());
+    }
+    if !args.flag_png.is_empty() {
+        view.png = Some(args.flag_png.as_str());
+    }
+    view.check = args.flag_check;
+
+    let mut renderer: Box<Renderer> = match args {
+        Args{flag_cpu: true, ref flag_png, ..} if !flag_png.is_empty() =>
+            Box::new(Cpu::offscreen(args.flag_size, args.flag_size)),
+        Args{flag_cpu: true, ..} | Args{flag_term: true, ..} => Box::new(Cpu::term(args.flag_graphics)),
+        Args{ref flag_png, ..} if !flag_png.is_empty() =>
+            Box::new(try!(gl::Gl::headless(args.flag_size, args.flag_size))),
+        Args{cmd_diff: true, ..} => Box::new(try!(gl::Gl::new(&format!("{} diff", NAME)))),
+        _ => Box::new(try!(gl::Gl::new(NAME))),
+    };
+    if args.arg_file.len() == 1 && !try!(open_member(&mut *renderer, &mut view, &args)) {
+        return Ok(());
+    }
 
-fn load_file_1d<F: ?Sized>(display: &F, width: u32, height: u32, path: &str)
-                           -> Result<glium::texture::Texture1d, LoadError>
-    where F: Facade + std::marker::Sized
-{
-    let read_bytes = std::cmp::min(8192, width * height);
-    println!("trying to read {:?} of {:?}", read_bytes, path);
+    view.minimap = !args.flag_no_minimap;
+    view.block = cmp::max(1, args.flag_block);
+    if args.flag_stride > 0 {
+        view.stride = Some(args.flag_stride);
+    }
+    view.auto_stride = args.flag_auto_stride;
+    if !args.flag_xor {
+        view.pixels = args.flag_pixels;
+        if args.flag_height > 0 {
+            view.frame_height = Some(args.flag_height);
+        }
+    }
+    view.bits = args.flag_bits;
+    if args.flag_format != Format::U8 {
+        try!(view.decode(args.flag_format));
+    }
+    if args.flag_strings {
+        try!(view.find_strings(args.flag_min_len));
+    }
+    if args.flag_entropy {
+        try!(view.find_entropy(cmp::max(1, args.flag_block)));
+    }
+    if !args.flag_search.is_empty() {
+        try!(view.search(&args.flag_search));
+    }
 
-    let mut buffer = Vec::new();
-    let f = try!(std::fs::File::open(path).map_err(LoadError::Io));
-    let mut chunk = f.take(read_bytes as u64);
-    use std::io::Read;
-    let bytes_read = try!(chunk.read_to_end(&mut buffer).map_err(LoadError::Io)) as u32;
-    println!("read {:?}", bytes_read);
+    render::run(&mut *renderer, &view)
+}
 
-    make_1d_texture(display, buffer)
+/// Lists the members of the input when it is an archive, then narrows `view` down to the
+/// one of `--entry` or picked from `--mosaic`. Says whether there is anything left to show.
+fn open_member(renderer: &mut Renderer, view: &mut View, args: &Args) -> Result<bool, Error> {
+    let not_found = |what: &str| {
+        Error::Input(args.arg_file[0].clone(), std::io::Error::new(std::io::ErrorKind::NotFound, what))
+    };
+    let members = match try!(view.members()) {
+        Some(members) => members,
+        None if args.flag_entry.is_empty() && !args.flag_mosaic => return Ok(true),
+        None => return Err(not_found("not a ZIP or TAR archive")),
+    };
+    for (i, member) in members.iter().enumerate() {
+        println!("{:>4} {:>10} {}", i + 1, member.size, member.name);
+    }
+    if members.is_empty() && (!args.flag_entry.is_empty() || args.flag_mosaic) {
+        return Err(not_found("empty archive"));
+    }
+    let picked = if !args.flag_entry.is_empty() {
+        match archive::find(&members, &args.flag_entry) {
+            Some(i) => i,
+            None => return Err(not_found(&format!("no member {}", args.flag_entry))),
+        }
+    } else if args.flag_mosaic {
+        match try!(render::mosaic(renderer, view, &members)) {
+            Some(i) => i,
+            None => return Ok(false),
+        }
+    } else {
+        return Ok(true);
+    };
+    try!(view.open_member(members[picked].clone()));
+    Ok(true)
 }
 
-fn make_1d_texture<F: ?Sized>(display: &F, buffer: std::vec::Vec<u8>)
-                              -> Result<glium::texture::Texture1d, LoadError>
+/// Draws the texture of `bytes_to_texture2d` coloured as `mode`.
+fn mode_program<F: ?Sized>(display: &F, mode: Mode) -> Result<glium::Program, Error>
     where F: Facade + std::marker::Sized
 {
-    let side = 3;
-    let mut buffers: Vec<(u8, u8, u8)> = vec![];
-    for slice in buffer.chunks(side) {
-        let vec = slice.to_vec();
-        if vec.len() == side {
-            buffers.push((vec[0], vec[1], vec[2]));
-        }
-    }
-
-    use glium::texture::Texture1d;
-    let texture = try!(Texture1d::with_format(display, buffer,
-                                              glium::texture::UncompressedFloatFormat::U8U8U8U8,
-                                              glium::texture::MipmapsOption::NoMipmap)
-                       .map_err(LoadError::Gl));
+    let fragment = match mode {
+        Mode::Bw => include_str!("frag_bw_2d_140.glsl"),
+        Mode::Four => include_str!("frag_four_2d_140.glsl"),
+        Mode::Detail => include_str!("frag_detail_2d_140.glsl"),
+        Mode::Class => include_str!("frag_class_2d_140.glsl"),
+    };
+    program!(display,
+             140 => {
+                 point_size: true,
+                 vertex: include_str!("vert_2d_140.glsl"),
+                 fragment: fragment,
+             })
+        .map_err(|e| Error::from_program(e, *display.get_context().get_opengl_version()))
+}
 
-    println!("texture info: {:?} {:?} {:?} {:?} {:?} {:?} {:?} {:?}"
-             ,texture.get_width()
-             ,texture.get_height()
-             ,texture.get_depth()
-             ,texture.kind()
-             ,texture.get_texture_type()
-             ,texture.get_mipmap_levels()
-             ,texture.get_samples()
-             ,texture.get_internal_format()
-            );
-    Ok(texture)
+/// Whether `meta` is that of a block device, such as a disk.
+#[cfg(unix)]
+fn is_block_device(meta: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+    meta.file_type().is_block_device()
 }
 
+#[cfg(not(unix))]
+fn is_block_device(_: &std::fs::Metadata) -> bool {
+    false
+}
 
-fn file_to_texture<F: ?Sized>(display: &F, width: u32, height: u32, path: &str) ->
-    Result<glium::texture::DepthTexture1d, LoadError>
-    where F: Facade + std::marker::Sized
-{
-    let read_bytes = std::cmp::min(8192, width * height);
-    println!("trying to read {:?} of {:?}", read_bytes, path);
-
-    let mut buffer: Vec<f32> = Vec::with_capacity(read_bytes as usize);
-    let f = try!(std::fs::File::open(path).map_err(LoadError::Io));
-    use std::io::Read;
-    for byte in f.take(read_bytes as u64).bytes() {
-        buffer.push((byte.unwrap() as f32) / 255f32);
+/// Size of the file at `path`, or of the block device.
+fn file_len(path: &str) -> Result<u64, Error> {
+    use std::io::{Seek, SeekFrom};
+    let meta = try!(std::fs::metadata(path).map_err(|e| Error::Input(path.to_owned(), e)));
+    if meta.is_dir() {
+        let e = std::io::Error::new(std::io::ErrorKind::InvalidInput, "is a directory");
+        return Err(Error::Input(path.to_owned(), e));
     }
-
-    make_depth1d_texture(display, buffer)
+    // The metadata of a device says nothing of its size, but it can be seeked to the end of.
+    if is_block_device(&meta) {
+        let mut f = try!(std::fs::File::open(path).map_err(|e| Error::Input(path.to_owned(), e)));
+        return f.seek(SeekFrom::End(0)).map_err(|e| Error::Input(path.to_owned(), e));
+    }
+    Ok(meta.len())
 }
 
 fn make_depth1d_texture<F: ?Sized>(display: &F, buffer: std::vec::Vec<f32>) ->
-    Result<glium::texture::DepthTexture1d, LoadError>
+    Result<glium::texture::DepthTexture1d, Error>
     where F: Facade + std::marker::Sized
 {
     let texture = try!(glium::texture::DepthTexture1d::new(display, buffer)
-                       .map_err(LoadError::Gl));
+                       .map_err(Error::Texture));
 
-    println!("texture info: {:?} {:?} {:?} {:?} {:?} {:?}"
+    debug!("texture info: {:?} {:?} {:?} {:?} {:?} {:?}"
              ,texture.get_width()
              ,texture.get_height()
              ,texture.get_depth()
@@ -230,79 +453,50 @@ fn make_depth1d_texture<F: ?Sized>(display: &F, buffer: std::vec::Vec<f32>) ->
     Ok(texture)
 }
 
-fn file_to_texture2d<F: ?Sized>(display: &F, width: u32, height: u32, path: &str) ->
-    Result<glium::texture::DepthTexture2d, LoadError>
+fn file_to_texture2d_<F: ?Sized>(display: &F, width: u32, height: u32, path: &str) ->
+    Result<glium::texture::DepthTexture2d, Error>
     where F: Facade + std::marker::Sized
 {
-    let read_bytes = std::cmp::min(8192, width * height);
-    println!("trying to read {:?} of {:?}", read_bytes, path);
-
-    let mut buffer: Vec<f32> = Vec::with_capacity(read_bytes as usize);
-    let f = try!(std::fs::File::open(path).map_err(LoadError::Io));
-    use std::io::Read;
-    for byte in f.take(read_bytes as u64).bytes() {
-        buffer.push((byte.unwrap() as f32) / 255f32);
-    }
-
-    make_depth2d_texture(display, buffer)
+    let buffer = try!(read_bytes(path, 0, (width * height) as u64));
+    bytes_to_texture2d(display, width, height, &buffer)
 }
 
-fn make_depth2d_texture<F: ?Sized>(display: &F, buffer: std::vec::Vec<f32>) ->
-    Result<glium::texture::DepthTexture2d, LoadError>
-    where F: Facade + std::marker::Sized
-{
-    // let side = (buffer.len() as f64).sqrt() as usize;
-    let side = buffer.len() / 64;
-    // let side = 4;
-    let mut buffers: Vec<Vec<f32>> = vec![];
-    for slice in buffer.chunks(side) {
-        let vec = slice.to_vec();
-        if vec.len() == side {
-            buffers.push(vec);
-        }
-    }
-    let texture = try!(glium::texture::DepthTexture2d::new(display, buffers)
-                       .map_err(LoadError::Gl));
+/// Reads at most `len` bytes of `path` starting at `offset`.
+fn read_bytes(path: &str, offset: u64, len: u64) -> Result<Vec<u8>, Error> {
+    debug!("trying to read {:?} of {:?} at {:?}", len, path, offset);
 
-    println!("texture info: {:?} {:?} {:?} {:?} {:?} {:?}"
-             ,texture.get_width()
-             ,texture.get_height()
-             ,texture.get_depth()
-             ,texture.kind()
-             ,texture.get_texture_type()
-             ,texture.get_mipmap_levels()
-            );
-    Ok(texture)
+    use std::io::{Read, Seek, SeekFrom};
+    let mut f = try!(std::fs::File::open(path).map_err(|e| Error::Input(path.to_owned(), e)));
+    try!(f.seek(SeekFrom::Start(offset)).map_err(|e| Error::Input(path.to_owned(), e)));
+    let mut handle = f.take(len);
+    let mut buffer: Vec<u8> = vec![];
+    let bytes_read = try!(handle.read_to_end(&mut buffer).map_err(|e| Error::Input(path.to_owned(), e)));
+    debug!("read {:?}", bytes_read);
+    Ok(buffer)
 }
 
-fn file_to_texture2d_<F: ?Sized>(display: &F, width: u32, height: u32, path: &str) ->
-    Result<glium::texture::DepthTexture2d, LoadError>
+/// Lays `buffer` out row-major, `width` bytes per row, in a `width`x`height` texture.
+/// Pixels past the end of `buffer` are zero.
+fn bytes_to_texture2d<F: ?Sized>(display: &F, width: u32, height: u32, buffer: &[u8]) ->
+    Result<glium::texture::DepthTexture2d, Error>
     where F: Facade + std::marker::Sized
 {
-    let read_bytes = width * height;//std::cmp::min(8192, width * height);
-    println!("trying to read {:?} of {:?}", read_bytes, path);
-
-    use std::io::Read;
-    let f = try!(std::fs::File::open(path).map_err(LoadError::Io));
-    let mut handle = f.take(read_bytes as u64);
-    let mut buffer: Vec<u8> = vec![];
-    let bytes_read = try!(handle.read_to_end(&mut buffer).map_err(LoadError::Io));
-    println!("read {:?}", bytes_read);
     let mut buffers: Vec<Vec<f32>> = vec![];
     let side = width as usize;
-    for slice in buffer.chunks(side) {
-        if slice.len() == side {
-            let vec = slice.into_iter().map(|f| *f as f32 / 255f32).collect();
-            buffers.push(vec);
-        }
+    for row in 0..height as usize {
+        let start = std::cmp::min(row * side, buffer.len());
+        let end = std::cmp::min(start + side, buffer.len());
+        let mut vec: Vec<f32> = buffer[start..end].iter().map(|f| *f as f32 / 255f32).collect();
+        vec.resize(side, 0f32);
+        buffers.push(vec);
     }
 
     let texture = try!(glium::texture::DepthTexture2d::with_format(display, buffers,
                                                                    glium::texture::DepthFormat::F32,
                                                                    glium::texture::MipmapsOption::NoMipmap)
-                       .map_err(LoadError::Gl));
+                       .map_err(Error::Texture));
 
-    println!("texture info: {:?} {:?} {:?} {:?} {:?} {:?}"
+    debug!("texture info: {:?} {:?} {:?} {:?} {:?} {:?}"
              ,texture.get_width()
              ,texture.get_height()
              ,texture.get_depth()
@@ -314,278 +508,20 @@ fn file_to_texture2d_<F: ?Sized>(display: &F, width: u32, height: u32, path: &st
 }
 
 fn detail_map<F: ?Sized>(display: &F) ->
-    Result<glium::texture::Texture1d, LoadError>
+    Result<glium::texture::Texture1d, Error>
     where F: Facade + std::marker::Sized
 {
-    let buffer = vec![
-        (0.0, 0.0, 0.0f32),
-        (0.0, 32.0/255.0, 32.0/255.0f32),
-        (32.0/255.0, 63.0/255.0, 31.0/255.0f32),
-        (63.0/255.0, 31.0/255.0, 32.0/255.0f32),
-        (64.0/255.0, 0.0, 0.0f32),
-        (127.0/255.0, 31.0/255.0, 32.0/255.0f32),
-        (95.0/255.0, 32.0/255.0, 63.0/255.0f32),
-        (64.0/255.0, 0.0, 64.0/255.0f32),
-        (96.0/255.0, 32.0/255.0, 64.0/255.0f32),
-        (127.0/255.0, 31.0/255.0, 96.0/255.0f32),
-        (95.0/255.0, 32.0/255.0, 127.0/255.0f32),
-        (63.0/255.0, 0.0, 127.0/255.0f32),
-        (31.0/255.0, 0.0, 95.0/255.0f32),
-        (32.0/255.0, 32.0/255.0, 64.0/255.0f32),
-        (31.0/255.0, 63.0/255.0, 96.0/255.0f32),
-        (63.0/255.0, 64.0/255.0, 127.0/255.0f32),
-        (31.0/255.0, 64.0/255.0, 95.0/255.0f32),
-        (32.0/255.0, 96.0/255.0, 64.0/255.0f32),
-        (31.0/255.0, 127.0/255.0, 96.0/255.0f32),
-        (64.0/255.0, 127.0/255.0, 127.0/255.0f32),
-        (96.0/255.0, 95.0/255.0, 127.0/255.0f32),
-        (127.0/255.0, 96.0/255.0, 95.0/255.0f32),
-        (95.0/255.0, 95.0/255.0, 64.0/255.0f32),
-        (64.0/255.0, 127.0/255.0, 63.0/255.0f32),
-        (96.0/255.0, 95.0/255.0, 63.0/255.0f32),
-        (127.0/255.0, 96.0/255.0, 31.0/255.0f32),
-        (95.0/255.0, 95.0/255.0, 0.0f32),
-        (63.0/255.0, 127.0/255.0, 0.0f32),
-        (63.0/255.0, 95.0/255.0, 32.0/255.0f32),
-        (31.0/255.0, 64.0/255.0, 31.0/255.0f32),
-        (0.0, 96.0/255.0, 32.0/255.0f32),
-        (0.0, 128.0/255.0, 0.0f32),
-        (32.0/255.0, 160.0/255.0, 0.0f32),
-        (63.0/255.0, 159.0/255.0, 32.0/255.0f32),
-        (31.0/255.0, 160.0/255.0, 63.0/255.0f32),
-        (0.0, 128.0/255.0, 64.0/255.0f32),
-        (32.0/255.0, 128.0/255.0, 96.0/255.0f32),
-        (31.0/255.0, 160.0/255.0, 127.0/255.0f32),
-        (32.0/255.0, 191.0/255.0, 95.0/255.0f32),
-        (0.0, 192.0/255.0, 64.0/255.0f32),
-        (32.0/255.0, 192.0/255.0, 96.0/255.0f32),
-        (31.0/255.0, 224.0/255.0, 127.0/255.0f32),
-        (32.0/255.0, 1.0, 95.0/255.0f32),
-        (0.0, 1.0, 63.0/255.0f32),
-        (0.0, 223.0/255.0, 31.0/255.0f32),
-        (32.0/255.0, 192.0/255.0, 32.0/255.0f32),
-        (63.0/255.0, 224.0/255.0, 31.0/255.0f32),
-        (64.0/255.0, 1.0, 63.0/255.0f32),
-        (64.0/255.0, 223.0/255.0, 31.0/255.0f32),
-        (96.0/255.0, 192.0/255.0, 32.0/255.0f32),
-        (127.0/255.0, 224.0/255.0, 31.0/255.0f32),
-        (127.0/255.0, 1.0, 64.0/255.0f32),
-        (95.0/255.0, 1.0, 96.0/255.0f32),
-        (96.0/255.0, 223.0/255.0, 127.0/255.0f32),
-        (95.0/255.0, 192.0/255.0, 95.0/255.0f32),
-        (127.0/255.0, 191.0/255.0, 64.0/255.0f32),
-        (95.0/255.0, 191.0/255.0, 96.0/255.0f32),
-        (96.0/255.0, 159.0/255.0, 127.0/255.0f32),
-        (95.0/255.0, 128.0/255.0, 95.0/255.0f32),
-        (127.0/255.0, 128.0/255.0, 63.0/255.0f32),
-        (95.0/255.0, 160.0/255.0, 63.0/255.0f32),
-        (64.0/255.0, 159.0/255.0, 31.0/255.0f32),
-        (96.0/255.0, 160.0/255.0, 0.0f32),
-        (128.0/255.0, 128.0/255.0, 0.0f32),
-        (160.0/255.0, 160.0/255.0, 0.0f32),
-        (191.0/255.0, 159.0/255.0, 32.0/255.0f32),
-        (159.0/255.0, 160.0/255.0, 63.0/255.0f32),
-        (128.0/255.0, 128.0/255.0, 64.0/255.0f32),
-        (160.0/255.0, 128.0/255.0, 96.0/255.0f32),
-        (159.0/255.0, 160.0/255.0, 127.0/255.0f32),
-        (160.0/255.0, 191.0/255.0, 95.0/255.0f32),
-        (128.0/255.0, 192.0/255.0, 64.0/255.0f32),
-        (160.0/255.0, 192.0/255.0, 96.0/255.0f32),
-        (159.0/255.0, 224.0/255.0, 127.0/255.0f32),
-        (160.0/255.0, 1.0, 95.0/255.0f32),
-        (128.0/255.0, 1.0, 63.0/255.0f32),
-        (128.0/255.0, 223.0/255.0, 31.0/255.0f32),
-        (160.0/255.0, 192.0/255.0, 32.0/255.0f32),
-        (191.0/255.0, 224.0/255.0, 31.0/255.0f32),
-        (192.0/255.0, 1.0, 63.0/255.0f32),
-        (192.0/255.0, 223.0/255.0, 31.0/255.0f32),
-        (224.0/255.0, 192.0/255.0, 32.0/255.0f32),
-        (1.0, 224.0/255.0, 31.0/255.0f32),
-        (1.0, 1.0, 64.0/255.0f32),
-        (223.0/255.0, 1.0, 96.0/255.0f32),
-        (224.0/255.0, 223.0/255.0, 127.0/255.0f32),
-        (223.0/255.0, 192.0/255.0, 95.0/255.0f32),
-        (1.0, 191.0/255.0, 64.0/255.0f32),
-        (223.0/255.0, 191.0/255.0, 96.0/255.0f32),
-        (224.0/255.0, 159.0/255.0, 127.0/255.0f32),
-        (223.0/255.0, 128.0/255.0, 95.0/255.0f32),
-        (1.0, 128.0/255.0, 63.0/255.0f32),
-        (223.0/255.0, 160.0/255.0, 63.0/255.0f32),
-        (192.0/255.0, 159.0/255.0, 31.0/255.0f32),
-        (224.0/255.0, 160.0/255.0, 0.0f32),
-        (1.0, 127.0/255.0, 0.0f32),
-        (223.0/255.0, 127.0/255.0, 32.0/255.0f32),
-        (224.0/255.0, 95.0/255.0, 63.0/255.0f32),
-        (223.0/255.0, 64.0/255.0, 31.0/255.0f32),
-        (1.0, 63.0/255.0, 0.0f32),
-        (1.0, 31.0/255.0, 32.0/255.0f32),
-        (223.0/255.0, 0.0, 31.0/255.0f32),
-        (192.0/255.0, 32.0/255.0, 32.0/255.0f32),
-        (191.0/255.0, 63.0/255.0, 0.0f32),
-        (191.0/255.0, 31.0/255.0, 32.0/255.0f32),
-        (159.0/255.0, 0.0, 31.0/255.0f32),
-        (128.0/255.0, 32.0/255.0, 32.0/255.0f32),
-        (128.0/255.0, 64.0/255.0, 0.0f32),
-        (160.0/255.0, 96.0/255.0, 0.0f32),
-        (191.0/255.0, 95.0/255.0, 32.0/255.0f32),
-        (159.0/255.0, 96.0/255.0, 63.0/255.0f32),
-        (128.0/255.0, 64.0/255.0, 64.0/255.0f32),
-        (160.0/255.0, 96.0/255.0, 64.0/255.0f32),
-        (191.0/255.0, 95.0/255.0, 96.0/255.0f32),
-        (159.0/255.0, 96.0/255.0, 127.0/255.0f32),
-        (128.0/255.0, 63.0/255.0, 127.0/255.0f32),
-        (128.0/255.0, 31.0/255.0, 95.0/255.0f32),
-        (160.0/255.0, 0.0, 96.0/255.0f32),
-        (191.0/255.0, 32.0/255.0, 95.0/255.0f32),
-        (192.0/255.0, 63.0/255.0, 127.0/255.0f32),
-        (192.0/255.0, 31.0/255.0, 95.0/255.0f32),
-        (224.0/255.0, 0.0, 96.0/255.0f32),
-        (1.0, 32.0/255.0, 95.0/255.0f32),
-        (1.0, 64.0/255.0, 127.0/255.0f32),
-        (223.0/255.0, 64.0/255.0, 95.0/255.0f32),
-        (224.0/255.0, 96.0/255.0, 64.0/255.0f32),
-        (223.0/255.0, 127.0/255.0, 96.0/255.0f32),
-        (1.0, 127.0/255.0, 128.0/255.0f32),
-        (223.0/255.0, 127.0/255.0, 160.0/255.0f32),
-        (224.0/255.0, 95.0/255.0, 191.0/255.0f32),
-        (223.0/255.0, 64.0/255.0, 159.0/255.0f32),
-        (1.0, 63.0/255.0, 128.0/255.0f32),
-        (1.0, 31.0/255.0, 160.0/255.0f32),
-        (223.0/255.0, 0.0, 159.0/255.0f32),
-        (192.0/255.0, 32.0/255.0, 160.0/255.0f32),
-        (191.0/255.0, 63.0/255.0, 128.0/255.0f32),
-        (191.0/255.0, 31.0/255.0, 160.0/255.0f32),
-        (159.0/255.0, 0.0, 159.0/255.0f32),
-        (128.0/255.0, 32.0/255.0, 160.0/255.0f32),
-        (128.0/255.0, 64.0/255.0, 128.0/255.0f32),
-        (160.0/255.0, 96.0/255.0, 128.0/255.0f32),
-        (191.0/255.0, 95.0/255.0, 160.0/255.0f32),
-        (159.0/255.0, 96.0/255.0, 191.0/255.0f32),
-        (128.0/255.0, 64.0/255.0, 192.0/255.0f32),
-        (160.0/255.0, 96.0/255.0, 192.0/255.0f32),
-        (191.0/255.0, 95.0/255.0, 224.0/255.0f32),
-        (159.0/255.0, 96.0/255.0, 1.0f32),
-        (128.0/255.0, 63.0/255.0, 1.0f32),
-        (128.0/255.0, 31.0/255.0, 223.0/255.0f32),
-        (160.0/255.0, 0.0, 224.0/255.0f32),
-        (191.0/255.0, 32.0/255.0, 223.0/255.0f32),
-        (192.0/255.0, 63.0/255.0, 1.0f32),
-        (192.0/255.0, 31.0/255.0, 223.0/255.0f32),
-        (224.0/255.0, 0.0, 224.0/255.0f32),
-        (1.0, 32.0/255.0, 223.0/255.0f32),
-        (1.0, 64.0/255.0, 1.0f32),
-        (223.0/255.0, 64.0/255.0, 223.0/255.0f32),
-        (224.0/255.0, 96.0/255.0, 192.0/255.0f32),
-        (223.0/255.0, 127.0/255.0, 224.0/255.0f32),
-        (1.0, 128.0/255.0, 1.0f32),
-        (223.0/255.0, 160.0/255.0, 1.0f32),
-        (192.0/255.0, 159.0/255.0, 223.0/255.0f32),
-        (224.0/255.0, 160.0/255.0, 192.0/255.0f32),
-        (1.0, 128.0/255.0, 191.0/255.0f32),
-        (223.0/255.0, 128.0/255.0, 159.0/255.0f32),
-        (224.0/255.0, 160.0/255.0, 128.0/255.0f32),
-        (223.0/255.0, 191.0/255.0, 160.0/255.0f32),
-        (1.0, 192.0/255.0, 191.0/255.0f32),
-        (223.0/255.0, 192.0/255.0, 159.0/255.0f32),
-        (224.0/255.0, 224.0/255.0, 128.0/255.0f32),
-        (223.0/255.0, 1.0, 160.0/255.0f32),
-        (1.0, 1.0, 192.0/255.0f32),
-        (1.0, 223.0/255.0, 224.0/255.0f32),
-        (223.0/255.0, 192.0/255.0, 223.0/255.0f32),
-        (192.0/255.0, 224.0/255.0, 224.0/255.0f32),
-        (191.0/255.0, 1.0, 192.0/255.0f32),
-        (191.0/255.0, 223.0/255.0, 224.0/255.0f32),
-        (159.0/255.0, 192.0/255.0, 223.0/255.0f32),
-        (128.0/255.0, 224.0/255.0, 224.0/255.0f32),
-        (128.0/255.0, 1.0, 191.0/255.0f32),
-        (160.0/255.0, 1.0, 159.0/255.0f32),
-        (159.0/255.0, 223.0/255.0, 128.0/255.0f32),
-        (160.0/255.0, 192.0/255.0, 160.0/255.0f32),
-        (128.0/255.0, 191.0/255.0, 191.0/255.0f32),
-        (160.0/255.0, 191.0/255.0, 159.0/255.0f32),
-        (159.0/255.0, 159.0/255.0, 128.0/255.0f32),
-        (160.0/255.0, 128.0/255.0, 160.0/255.0f32),
-        (128.0/255.0, 128.0/255.0, 192.0/255.0f32),
-        (160.0/255.0, 160.0/255.0, 192.0/255.0f32),
-        (191.0/255.0, 159.0/255.0, 224.0/255.0f32),
-        (159.0/255.0, 160.0/255.0, 1.0f32),
-        (127.0/255.0, 128.0/255.0, 1.0f32),
-        (95.0/255.0, 160.0/255.0, 1.0f32),
-        (64.0/255.0, 159.0/255.0, 223.0/255.0f32),
-        (96.0/255.0, 160.0/255.0, 192.0/255.0f32),
-        (127.0/255.0, 128.0/255.0, 191.0/255.0f32),
-        (95.0/255.0, 128.0/255.0, 159.0/255.0f32),
-        (96.0/255.0, 160.0/255.0, 128.0/255.0f32),
-        (95.0/255.0, 191.0/255.0, 160.0/255.0f32),
-        (127.0/255.0, 192.0/255.0, 191.0/255.0f32),
-        (95.0/255.0, 192.0/255.0, 159.0/255.0f32),
-        (96.0/255.0, 224.0/255.0, 128.0/255.0f32),
-        (95.0/255.0, 1.0, 160.0/255.0f32),
-        (127.0/255.0, 1.0, 192.0/255.0f32),
-        (127.0/255.0, 223.0/255.0, 224.0/255.0f32),
-        (95.0/255.0, 192.0/255.0, 223.0/255.0f32),
-        (64.0/255.0, 224.0/255.0, 224.0/255.0f32),
-        (63.0/255.0, 1.0, 192.0/255.0f32),
-        (63.0/255.0, 223.0/255.0, 224.0/255.0f32),
-        (31.0/255.0, 192.0/255.0, 223.0/255.0f32),
-        (0.0, 224.0/255.0, 224.0/255.0f32),
-        (0.0, 1.0, 191.0/255.0f32),
-        (32.0/255.0, 1.0, 159.0/255.0f32),
-        (31.0/255.0, 223.0/255.0, 128.0/255.0f32),
-        (32.0/255.0, 192.0/255.0, 160.0/255.0f32),
-        (0.0, 191.0/255.0, 191.0/255.0f32),
-        (32.0/255.0, 191.0/255.0, 159.0/255.0f32),
-        (31.0/255.0, 159.0/255.0, 128.0/255.0f32),
-        (32.0/255.0, 128.0/255.0, 160.0/255.0f32),
-        (0.0, 128.0/255.0, 192.0/255.0f32),
-        (32.0/255.0, 160.0/255.0, 192.0/255.0f32),
-        (63.0/255.0, 159.0/255.0, 224.0/255.0f32),
-        (31.0/255.0, 160.0/255.0, 1.0f32),
-        (0.0, 127.0/255.0, 1.0f32),
-        (0.0, 95.0/255.0, 223.0/255.0f32),
-        (32.0/255.0, 64.0/255.0, 224.0/255.0f32),
-        (63.0/255.0, 96.0/255.0, 223.0/255.0f32),
-        (64.0/255.0, 127.0/255.0, 1.0f32),
-        (96.0/255.0, 95.0/255.0, 1.0f32),
-        (127.0/255.0, 96.0/255.0, 223.0/255.0f32),
-        (95.0/255.0, 95.0/255.0, 192.0/255.0f32),
-        (64.0/255.0, 127.0/255.0, 191.0/255.0f32),
-        (96.0/255.0, 95.0/255.0, 191.0/255.0f32),
-        (127.0/255.0, 96.0/255.0, 159.0/255.0f32),
-        (95.0/255.0, 95.0/255.0, 128.0/255.0f32),
-        (63.0/255.0, 127.0/255.0, 128.0/255.0f32),
-        (31.0/255.0, 127.0/255.0, 160.0/255.0f32),
-        (32.0/255.0, 95.0/255.0, 191.0/255.0f32),
-        (31.0/255.0, 64.0/255.0, 159.0/255.0f32),
-        (63.0/255.0, 63.0/255.0, 128.0/255.0f32),
-        (31.0/255.0, 63.0/255.0, 160.0/255.0f32),
-        (32.0/255.0, 31.0/255.0, 191.0/255.0f32),
-        (31.0/255.0, 0.0, 159.0/255.0f32),
-        (64.0/255.0, 0.0, 128.0/255.0f32),
-        (96.0/255.0, 32.0/255.0, 128.0/255.0f32),
-        (127.0/255.0, 31.0/255.0, 160.0/255.0f32),
-        (95.0/255.0, 32.0/255.0, 191.0/255.0f32),
-        (64.0/255.0, 0.0, 192.0/255.0f32),
-        (96.0/255.0, 32.0/255.0, 192.0/255.0f32),
-        (127.0/255.0, 31.0/255.0, 224.0/255.0f32),
-        (95.0/255.0, 32.0/255.0, 1.0f32),
-        (63.0/255.0, 0.0, 1.0f32),
-        (63.0/255.0, 32.0/255.0, 223.0/255.0f32),
-        (31.0/255.0, 63.0/255.0, 224.0/255.0f32),
-        (96.0/255.0, 32.0/255.0, 0.0f32),
-        (1.0, 1.0, 1.0f32),
-    ];
-
-    let texture = try!(glium::texture::Texture1d::new(display, buffer)
-                       .map_err(LoadError::Gl));
+
+    let texture = try!(glium::texture::Texture1d::new(display, palette::DETAIL.to_vec())
+                       .map_err(Error::Texture));
 
     // use glium::texture::Texture1d;
     // let texture = try!(Texture1d::with_format(display, buffer,
     //                                           glium::texture::UncompressedFloatFormat::U8U8U8U8,
     //                                           glium::texture::MipmapsOption::NoMipmap)
-    //                    .map_err(LoadError::Gl));
+    //                    .map_err(Error::Texture));
 
-    println!("texture info: w:{:?} h:{:?} d:{:?} kind:{:?} type:{:?} lvls:{:?}"
+    debug!("texture info: w:{:?} h:{:?} d:{:?} kind:{:?} type:{:?} lvls:{:?}"
              ,texture.get_width()
              ,texture.get_height()
              ,texture.get_depth()
@@ -0,0 +1,102 @@
+//! The whole input in a strip along the right edge of the window, downsampled as
+//! `raster::overview` does for thumbnails, with the bytes on screen framed. Only the
+//! first `SAMPLE` bytes behind each pixel are read, so that a large file doesn't have
+//! to be read through before the first frame.
+
+use std::cmp;
+use std::fs;
+
+use error::Error;
+use palette::{self, Mode, Rgb};
+use raster::{self, Image};
+use source::Source;
+use super::is_block_device;
+
+/// Pixels across the strip.
+pub const WIDTH: u32 = 32;
+
+/// Most bytes read for one pixel.
+const SAMPLE: u64 = 256;
+
+const FRAME: Rgb = [255, 255, 255];
+
+pub struct Minimap {
+    image: Image,
+    /// Bytes in the input.
+    len: u64,
+}
+
+/// Whether `source` can be read here and there for its minimap: not a device or the
+/// memory of a process, where reads are slow or may have side effects, nor compressed,
+/// where each read decompresses all that comes before it.
+pub fn can_sample(source: &Source) -> bool {
+    let deflated = source.member.as_ref().map_or(false, |member| member.deflated);
+    if source.process.is_some() || source.codec.is_some() || deflated {
+        return false;
+    }
+    fs::metadata(source.path).map(|meta| !is_block_device(&meta)).unwrap_or(false)
+}
+
+impl Minimap {
+    /// All of the `len` bytes of `source` in a strip `height` pixels high, each pixel
+    /// in the average colour of the first bytes it covers.
+    pub fn new(source: &Source, mode: Mode, height: u32, len: u64) -> Result<Minimap, Error> {
+        let n = (WIDTH * height) as u64;
+        if len <= n {
+            let bytes = try!(source.read(0, n));
+            return Ok(Minimap{image: raster::bytes(mode, WIDTH, height, &bytes), len: len});
+        }
+        let mut image = Image::new(WIDTH, height);
+        for pixel in 0..n {
+            let (start, end) = (pixel * len / n, (pixel + 1) * len / n);
+            let bytes = try!(source.read(start, cmp::min(end - start, SAMPLE)));
+            if bytes.is_empty() {
+                break;
+            }
+            let mut sums = [0u64; 3];
+            for &byte in &bytes {
+                let c = palette::colour(mode, byte);
+                for i in 0..3 {
+                    sums[i] += c[i] as u64;
+                }
+            }
+            let count = bytes.len() as u64;
+            image.pixels[pixel as usize] = [(sums[0] / count) as u8, (sums[1] / count) as u8, (sums[2] / count) as u8];
+        }
+        Ok(Minimap{image: image, len: len})
+    }
+
+    /// Bytes behind each pixel, or behind less than a pixel for the smallest inputs.
+    fn scale(&self) -> u64 {
+        cmp::max(self.len, (self.image.width * self.image.height) as u64)
+    }
+
+    /// The pixel showing the byte at `offset`, counting row by row from the top left.
+    fn pixel(&self, offset: u64) -> u64 {
+        offset * (self.image.width * self.image.height) as u64 / self.scale()
+    }
+
+    /// The offset of the first byte behind the pixel at `x`, `y`, if there is one.
+    pub fn offset(&self, x: u32, y: u32) -> Option<u64> {
+        if x >= self.image.width || y >= self.image.height {
+            return None;
+        }
+        let pixel = (y * self.image.width + x) as u64;
+        let offset = pixel * self.scale() / (self.image.width * self.image.height) as u64;
+        if offset < self.len { Some(offset) } else { None }
+    }
+
+    /// The strip with a frame around the rows showing the `page` bytes from `offset`.
+    pub fn draw(&self, offset: u64, page: u64) -> Image {
+        let mut image = self.image.clone();
+        let (width, height) = (image.width, image.height);
+        let top = cmp::min(self.pixel(offset) / width as u64, height as u64 - 1) as u32;
+        let end = cmp::min(offset + page, self.len);
+        let bottom = cmp::min(self.pixel(end - 1) / width as u64, height as u64 - 1) as u32;
+        image.fill(0, top, width, 1, FRAME);
+        image.fill(0, bottom, width, 1, FRAME);
+        image.fill(0, top, 1, bottom - top + 1, FRAME);
+        image.fill(width - 1, top, 1, bottom - top + 1, FRAME);
+        image
+    }
+}
@@ -0,0 +1,67 @@
+//! Thumbnails of all the members of an archive in a grid that fills the window, each
+//! named under it, to pick one of them from.
+
+use std::cmp;
+
+use error::Error;
+use font;
+use palette::{Mode, Rgb};
+use raster::{self, Image};
+use source::Source;
+
+const LABEL_INK: Rgb = [192, 192, 192];
+
+pub struct Mosaic {
+    pub image: Image,
+    columns: u32,
+    /// Pixels across each thumbnail and down to the next row of them, labels included.
+    tile: u32,
+    count: usize,
+}
+
+/// The columns, and pixels across each tile, of the grid with the largest square tiles
+/// that `count` of fit in `width`x`height` with `label` pixels under each.
+pub fn grid(count: usize, width: u32, height: u32, label: u32) -> Option<(u32, u32)> {
+    (1..count as u32 + 1)
+        .map(|columns| {
+            let rows = (count as u32 + columns - 1) / columns;
+            (columns, cmp::min(width / columns, (height / rows).saturating_sub(label)))
+        })
+        .filter(|&(_, tile)| tile > 0)
+        .fold(None, |best, (columns, tile)| match best {
+            Some((_, best_tile)) if best_tile >= tile => best,
+            _ => Some((columns, tile)),
+        })
+}
+
+impl Mosaic {
+    /// Each of `sources` all in one thumbnail, named after `names`.
+    pub fn new(sources: &[Source], names: &[String], mode: Mode, width: u32, height: u32)
+               -> Result<Mosaic, Error> {
+        let (columns, tile) = match grid(sources.len(), width, height, font::HEIGHT) {
+            Some(grid) => grid,
+            None => return Err(Error::Layout(width, height)),
+        };
+        let mut image = Image::new(width, height);
+        for (i, (source, name)) in sources.iter().zip(names).enumerate() {
+            let (left, top) = (i as u32 % columns * tile, i as u32 / columns * (tile + font::HEIGHT));
+            // A pixel between thumbnails keeps them apart.
+            let side = cmp::max(tile - 1, 1);
+            let len = try!(source.len());
+            if len > 0 {
+                let thumb = try!(raster::overview(mode, side, side, try!(source.open()), len)
+                                 .map_err(|e| source.error(e)));
+                image.blit(&thumb, left, top);
+            }
+            image.text(left, top + tile, &font::tail(name, tile), LABEL_INK);
+        }
+        Ok(Mosaic{image: image, columns: columns, tile: tile, count: sources.len()})
+    }
+
+    /// Which thumbnail, or label, is at `x`, `y`.
+    pub fn pick(&self, x: u32, y: u32) -> Option<usize> {
+        let (column, row) = (x / self.tile, y / (self.tile + font::HEIGHT));
+        let i = (row * self.columns + column) as usize;
+        if column < self.columns && i < self.count { Some(i) } else { None }
+    }
+}
@@ -0,0 +1,362 @@
+//! Byte to colour mappings, mirroring the `frag_*_2d_140.glsl` shaders on the CPU.
+
+use classify::Class;
+
+/// How a byte value is turned into a colour.
+#[derive(Debug, Clone, Copy, PartialEq, RustcDecodable)]
+pub enum Mode {
+    /// Grey level: the byte value itself.
+    Bw,
+    /// Zero, 0xff, ASCII text and anything else each get a colour.
+    Four,
+    /// One colour per byte value, from `DETAIL`.
+    Detail,
+    /// A tint for the `classify::Class` of each block, brightened by the byte value.
+    Class,
+}
+
+/// Colour of each byte value in `Mode::Detail`.
+pub const DETAIL: [(f32, f32, f32); 256] = [
+    (0.0, 0.0, 0.0f32),
+    (0.0, 32.0/255.0, 32.0/255.0f32),
+    (32.0/255.0, 63.0/255.0, 31.0/255.0f32),
+    (63.0/255.0, 31.0/255.0, 32.0/255.0f32),
+    (64.0/255.0, 0.0, 0.0f32),
+    (127.0/255.0, 31.0/255.0, 32.0/255.0f32),
+    (95.0/255.0, 32.0/255.0, 63.0/255.0f32),
+    (64.0/255.0, 0.0, 64.0/255.0f32),
+    (96.0/255.0, 32.0/255.0, 64.0/255.0f32),
+    (127.0/255.0, 31.0/255.0, 96.0/255.0f32),
+    (95.0/255.0, 32.0/255.0, 127.0/255.0f32),
+    (63.0/255.0, 0.0, 127.0/255.0f32),
+    (31.0/255.0, 0.0, 95.0/255.0f32),
+    (32.0/255.0, 32.0/255.0, 64.0/255.0f32),
+    (31.0/255.0, 63.0/255.0, 96.0/255.0f32),
+    (63.0/255.0, 64.0/255.0, 127.0/255.0f32),
+    (31.0/255.0, 64.0/255.0, 95.0/255.0f32),
+    (32.0/255.0, 96.0/255.0, 64.0/255.0f32),
+    (31.0/255.0, 127.0/255.0, 96.0/255.0f32),
+    (64.0/255.0, 127.0/255.0, 127.0/255.0f32),
+    (96.0/255.0, 95.0/255.0, 127.0/255.0f32),
+    (127.0/255.0, 96.0/255.0, 95.0/255.0f32),
+    (95.0/255.0, 95.0/255.0, 64.0/255.0f32),
+    (64.0/255.0, 127.0/255.0, 63.0/255.0f32),
+    (96.0/255.0, 95.0/255.0, 63.0/255.0f32),
+    (127.0/255.0, 96.0/255.0, 31.0/255.0f32),
+    (95.0/255.0, 95.0/255.0, 0.0f32),
+    (63.0/255.0, 127.0/255.0, 0.0f32),
+    (63.0/255.0, 95.0/255.0, 32.0/255.0f32),
+    (31.0/255.0, 64.0/255.0, 31.0/255.0f32),
+    (0.0, 96.0/255.0, 32.0/255.0f32),
+    (0.0, 128.0/255.0, 0.0f32),
+    (32.0/255.0, 160.0/255.0, 0.0f32),
+    (63.0/255.0, 159.0/255.0, 32.0/255.0f32),
+    (31.0/255.0, 160.0/255.0, 63.0/255.0f32),
+    (0.0, 128.0/255.0, 64.0/255.0f32),
+    (32.0/255.0, 128.0/255.0, 96.0/255.0f32),
+    (31.0/255.0, 160.0/255.0, 127.0/255.0f32),
+    (32.0/255.0, 191.0/255.0, 95.0/255.0f32),
+    (0.0, 192.0/255.0, 64.0/255.0f32),
+    (32.0/255.0, 192.0/255.0, 96.0/255.0f32),
+    (31.0/255.0, 224.0/255.0, 127.0/255.0f32),
+    (32.0/255.0, 1.0, 95.0/255.0f32),
+    (0.0, 1.0, 63.0/255.0f32),
+    (0.0, 223.0/255.0, 31.0/255.0f32),
+    (32.0/255.0, 192.0/255.0, 32.0/255.0f32),
+    (63.0/255.0, 224.0/255.0, 31.0/255.0f32),
+    (64.0/255.0, 1.0, 63.0/255.0f32),
+    (64.0/255.0, 223.0/255.0, 31.0/255.0f32),
+    (96.0/255.0, 192.0/255.0, 32.0/255.0f32),
+    (127.0/255.0, 224.0/255.0, 31.0/255.0f32),
+    (127.0/255.0, 1.0, 64.0/255.0f32),
+    (95.0/255.0, 1.0, 96.0/255.0f32),
+    (96.0/255.0, 223.0/255.0, 127.0/255.0f32),
+    (95.0/255.0, 192.0/255.0, 95.0/255.0f32),
+    (127.0/255.0, 191.0/255.0, 64.0/255.0f32),
+    (95.0/255.0, 191.0/255.0, 96.0/255.0f32),
+    (96.0/255.0, 159.0/255.0, 127.0/255.0f32),
+    (95.0/255.0, 128.0/255.0, 95.0/255.0f32),
+    (127.0/255.0, 128.0/255.0, 63.0/255.0f32),
+    (95.0/255.0, 160.0/255.0, 63.0/255.0f32),
+    (64.0/255.0, 159.0/255.0, 31.0/255.0f32),
+    (96.0/255.0, 160.0/255.0, 0.0f32),
+    (128.0/255.0, 128.0/255.0, 0.0f32),
+    (160.0/255.0, 160.0/255.0, 0.0f32),
+    (191.0/255.0, 159.0/255.0, 32.0/255.0f32),
+    (159.0/255.0, 160.0/255.0, 63.0/255.0f32),
+    (128.0/255.0, 128.0/255.0, 64.0/255.0f32),
+    (160.0/255.0, 128.0/255.0, 96.0/255.0f32),
+    (159.0/255.0, 160.0/255.0, 127.0/255.0f32),
+    (160.0/255.0, 191.0/255.0, 95.0/255.0f32),
+    (128.0/255.0, 192.0/255.0, 64.0/255.0f32),
+    (160.0/255.0, 192.0/255.0, 96.0/255.0f32),
+    (159.0/255.0, 224.0/255.0, 127.0/255.0f32),
+    (160.0/255.0, 1.0, 95.0/255.0f32),
+    (128.0/255.0, 1.0, 63.0/255.0f32),
+    (128.0/255.0, 223.0/255.0, 31.0/255.0f32),
+    (160.0/255.0, 192.0/255.0, 32.0/255.0f32),
+    (191.0/255.0, 224.0/255.0, 31.0/255.0f32),
+    (192.0/255.0, 1.0, 63.0/255.0f32),
+    (192.0/255.0, 223.0/255.0, 31.0/255.0f32),
+    (224.0/255.0, 192.0/255.0, 32.0/255.0f32),
+    (1.0, 224.0/255.0, 31.0/255.0f32),
+    (1.0, 1.0, 64.0/255.0f32),
+    (223.0/255.0, 1.0, 96.0/255.0f32),
+    (224.0/255.0, 223.0/255.0, 127.0/255.0f32),
+    (223.0/255.0, 192.0/255.0, 95.0/255.0f32),
+    (1.0, 191.0/255.0, 64.0/255.0f32),
+    (223.0/255.0, 191.0/255.0, 96.0/255.0f32),
+    (224.0/255.0, 159.0/255.0, 127.0/255.0f32),
+    (223.0/255.0, 128.0/255.0, 95.0/255.0f32),
+    (1.0, 128.0/255.0, 63.0/255.0f32),
+    (223.0/255.0, 160.0/255.0, 63.0/255.0f32),
+    (192.0/255.0, 159.0/255.0, 31.0/255.0f32),
+    (224.0/255.0, 160.0/255.0, 0.0f32),
+    (1.0, 127.0/255.0, 0.0f32),
+    (223.0/255.0, 127.0/255.0, 32.0/255.0f32),
+    (224.0/255.0, 95.0/255.0, 63.0/255.0f32),
+    (223.0/255.0, 64.0/255.0, 31.0/255.0f32),
+    (1.0, 63.0/255.0, 0.0f32),
+    (1.0, 31.0/255.0, 32.0/255.0f32),
+    (223.0/255.0, 0.0, 31.0/255.0f32),
+    (192.0/255.0, 32.0/255.0, 32.0/255.0f32),
+    (191.0/255.0, 63.0/255.0, 0.0f32),
+    (191.0/255.0, 31.0/255.0, 32.0/255.0f32),
+    (159.0/255.0, 0.0, 31.0/255.0f32),
+    (128.0/255.0, 32.0/255.0, 32.0/255.0f32),
+    (128.0/255.0, 64.0/255.0, 0.0f32),
+    (160.0/255.0, 96.0/255.0, 0.0f32),
+    (191.0/255.0, 95.0/255.0, 32.0/255.0f32),
+    (159.0/255.0, 96.0/255.0, 63.0/255.0f32),
+    (128.0/255.0, 64.0/255.0, 64.0/255.0f32),
+    (160.0/255.0, 96.0/255.0, 64.0/255.0f32),
+    (191.0/255.0, 95.0/255.0, 96.0/255.0f32),
+    (159.0/255.0, 96.0/255.0, 127.0/255.0f32),
+    (128.0/255.0, 63.0/255.0, 127.0/255.0f32),
+    (128.0/255.0, 31.0/255.0, 95.0/255.0f32),
+    (160.0/255.0, 0.0, 96.0/255.0f32),
+    (191.0/255.0, 32.0/255.0, 95.0/255.0f32),
+    (192.0/255.0, 63.0/255.0, 127.0/255.0f32),
+    (192.0/255.0, 31.0/255.0, 95.0/255.0f32),
+    (224.0/255.0, 0.0, 96.0/255.0f32),
+    (1.0, 32.0/255.0, 95.0/255.0f32),
+    (1.0, 64.0/255.0, 127.0/255.0f32),
+    (223.0/255.0, 64.0/255.0, 95.0/255.0f32),
+    (224.0/255.0, 96.0/255.0, 64.0/255.0f32),
+    (223.0/255.0, 127.0/255.0, 96.0/255.0f32),
+    (1.0, 127.0/255.0, 128.0/255.0f32),
+    (223.0/255.0, 127.0/255.0, 160.0/255.0f32),
+    (224.0/255.0, 95.0/255.0, 191.0/255.0f32),
+    (223.0/255.0, 64.0/255.0, 159.0/255.0f32),
+    (1.0, 63.0/255.0, 128.0/255.0f32),
+    (1.0, 31.0/255.0, 160.0/255.0f32),
+    (223.0/255.0, 0.0, 159.0/255.0f32),
+    (192.0/255.0, 32.0/255.0, 160.0/255.0f32),
+    (191.0/255.0, 63.0/255.0, 128.0/255.0f32),
+    (191.0/255.0, 31.0/255.0, 160.0/255.0f32),
+    (159.0/255.0, 0.0, 159.0/255.0f32),
+    (128.0/255.0, 32.0/255.0, 160.0/255.0f32),
+    (128.0/255.0, 64.0/255.0, 128.0/255.0f32),
+    (160.0/255.0, 96.0/255.0, 128.0/255.0f32),
+    (191.0/255.0, 95.0/255.0, 160.0/255.0f32),
+    (159.0/255.0, 96.0/255.0, 191.0/255.0f32),
+    (128.0/255.0, 64.0/255.0, 192.0/255.0f32),
+    (160.0/255.0, 96.0/255.0, 192.0/255.0f32),
+    (191.0/255.0, 95.0/255.0, 224.0/255.0f32),
+    (159.0/255.0, 96.0/255.0, 1.0f32),
+    (128.0/255.0, 63.0/255.0, 1.0f32),
+    (128.0/255.0, 31.0/255.0, 223.0/255.0f32),
+    (160.0/255.0, 0.0, 224.0/255.0f32),
+    (191.0/255.0, 32.0/255.0, 223.0/255.0f32),
+    (192.0/255.0, 63.0/255.0, 1.0f32),
+    (192.0/255.0, 31.0/255.0, 223.0/255.0f32),
+    (224.0/255.0, 0.0, 224.0/255.0f32),
+    (1.0, 32.0/255.0, 223.0/255.0f32),
+    (1.0, 64.0/255.0, 1.0f32),
+    (223.0/255.0, 64.0/255.0, 223.0/255.0f32),
+    (224.0/255.0, 96.0/255.0, 192.0/255.0f32),
+    (223.0/255.0, 127.0/255.0, 224.0/255.0f32),
+    (1.0, 128.0/255.0, 1.0f32),
+    (223.0/255.0, 160.0/255.0, 1.0f32),
+    (192.0/255.0, 159.0/255.0, 223.0/255.0f32),
+    (224.0/255.0, 160.0/255.0, 192.0/255.0f32),
+    (1.0, 128.0/255.0, 191.0/255.0f32),
+    (223.0/255.0, 128.0/255.0, 159.0/255.0f32),
+    (224.0/255.0, 160.0/255.0, 128.0/255.0f32),
+    (223.0/255.0, 191.0/255.0, 160.0/255.0f32),
+    (1.0, 192.0/255.0, 191.0/255.0f32),
+    (223.0/255.0, 192.0/255.0, 159.0/255.0f32),
+    (224.0/255.0, 224.0/255.0, 128.0/255.0f32),
+    (223.0/255.0, 1.0, 160.0/255.0f32),
+    (1.0, 1.0, 192.0/255.0f32),
+    (1.0, 223.0/255.0, 224.0/255.0f32),
+    (223.0/255.0, 192.0/255.0, 223.0/255.0f32),
+    (192.0/255.0, 224.0/255.0, 224.0/255.0f32),
+    (191.0/255.0, 1.0, 192.0/255.0f32),
+    (191.0/255.0, 223.0/255.0, 224.0/255.0f32),
+    (159.0/255.0, 192.0/255.0, 223.0/255.0f32),
+    (128.0/255.0, 224.0/255.0, 224.0/255.0f32),
+    (128.0/255.0, 1.0, 191.0/255.0f32),
+    (160.0/255.0, 1.0, 159.0/255.0f32),
+    (159.0/255.0, 223.0/255.0, 128.0/255.0f32),
+    (160.0/255.0, 192.0/255.0, 160.0/255.0f32),
+    (128.0/255.0, 191.0/255.0, 191.0/255.0f32),
+    (160.0/255.0, 191.0/255.0, 159.0/255.0f32),
+    (159.0/255.0, 159.0/255.0, 128.0/255.0f32),
+    (160.0/255.0, 128.0/255.0, 160.0/255.0f32),
+    (128.0/255.0, 128.0/255.0, 192.0/255.0f32),
+    (160.0/255.0, 160.0/255.0, 192.0/255.0f32),
+    (191.0/255.0, 159.0/255.0, 224.0/255.0f32),
+    (159.0/255.0, 160.0/255.0, 1.0f32),
+    (127.0/255.0, 128.0/255.0, 1.0f32),
+    (95.0/255.0, 160.0/255.0, 1.0f32),
+    (64.0/255.0, 159.0/255.0, 223.0/255.0f32),
+    (96.0/255.0, 160.0/255.0, 192.0/255.0f32),
+    (127.0/255.0, 128.0/255.0, 191.0/255.0f32),
+    (95.0/255.0, 128.0/255.0, 159.0/255.0f32),
+    (96.0/255.0, 160.0/255.0, 128.0/255.0f32),
+    (95.0/255.0, 191.0/255.0, 160.0/255.0f32),
+    (127.0/255.0, 192.0/255.0, 191.0/255.0f32),
+    (95.0/255.0, 192.0/255.0, 159.0/255.0f32),
+    (96.0/255.0, 224.0/255.0, 128.0/255.0f32),
+    (95.0/255.0, 1.0, 160.0/255.0f32),
+    (127.0/255.0, 1.0, 192.0/255.0f32),
+    (127.0/255.0, 223.0/255.0, 224.0/255.0f32),
+    (95.0/255.0, 192.0/255.0, 223.0/255.0f32),
+    (64.0/255.0, 224.0/255.0, 224.0/255.0f32),
+    (63.0/255.0, 1.0, 192.0/255.0f32),
+    (63.0/255.0, 223.0/255.0, 224.0/255.0f32),
+    (31.0/255.0, 192.0/255.0, 223.0/255.0f32),
+    (0.0, 224.0/255.0, 224.0/255.0f32),
+    (0.0, 1.0, 191.0/255.0f32),
+    (32.0/255.0, 1.0, 159.0/255.0f32),
+    (31.0/255.0, 223.0/255.0, 128.0/255.0f32),
+    (32.0/255.0, 192.0/255.0, 160.0/255.0f32),
+    (0.0, 191.0/255.0, 191.0/255.0f32),
+    (32.0/255.0, 191.0/255.0, 159.0/255.0f32),
+    (31.0/255.0, 159.0/255.0, 128.0/255.0f32),
+    (32.0/255.0, 128.0/255.0, 160.0/255.0f32),
+    (0.0, 128.0/255.0, 192.0/255.0f32),
+    (32.0/255.0, 160.0/255.0, 192.0/255.0f32),
+    (63.0/255.0, 159.0/255.0, 224.0/255.0f32),
+    (31.0/255.0, 160.0/255.0, 1.0f32),
+    (0.0, 127.0/255.0, 1.0f32),
+    (0.0, 95.0/255.0, 223.0/255.0f32),
+    (32.0/255.0, 64.0/255.0, 224.0/255.0f32),
+    (63.0/255.0, 96.0/255.0, 223.0/255.0f32),
+    (64.0/255.0, 127.0/255.0, 1.0f32),
+    (96.0/255.0, 95.0/255.0, 1.0f32),
+    (127.0/255.0, 96.0/255.0, 223.0/255.0f32),
+    (95.0/255.0, 95.0/255.0, 192.0/255.0f32),
+    (64.0/255.0, 127.0/255.0, 191.0/255.0f32),
+    (96.0/255.0, 95.0/255.0, 191.0/255.0f32),
+    (127.0/255.0, 96.0/255.0, 159.0/255.0f32),
+    (95.0/255.0, 95.0/255.0, 128.0/255.0f32),
+    (63.0/255.0, 127.0/255.0, 128.0/255.0f32),
+    (31.0/255.0, 127.0/255.0, 160.0/255.0f32),
+    (32.0/255.0, 95.0/255.0, 191.0/255.0f32),
+    (31.0/255.0, 64.0/255.0, 159.0/255.0f32),
+    (63.0/255.0, 63.0/255.0, 128.0/255.0f32),
+    (31.0/255.0, 63.0/255.0, 160.0/255.0f32),
+    (32.0/255.0, 31.0/255.0, 191.0/255.0f32),
+    (31.0/255.0, 0.0, 159.0/255.0f32),
+    (64.0/255.0, 0.0, 128.0/255.0f32),
+    (96.0/255.0, 32.0/255.0, 128.0/255.0f32),
+    (127.0/255.0, 31.0/255.0, 160.0/255.0f32),
+    (95.0/255.0, 32.0/255.0, 191.0/255.0f32),
+    (64.0/255.0, 0.0, 192.0/255.0f32),
+    (96.0/255.0, 32.0/255.0, 192.0/255.0f32),
+    (127.0/255.0, 31.0/255.0, 224.0/255.0f32),
+    (95.0/255.0, 32.0/255.0, 1.0f32),
+    (63.0/255.0, 0.0, 1.0f32),
+    (63.0/255.0, 32.0/255.0, 223.0/255.0f32),
+    (31.0/255.0, 63.0/255.0, 224.0/255.0f32),
+    (96.0/255.0, 32.0/255.0, 0.0f32),
+    (1.0, 1.0, 1.0f32),
+];
+
+pub type Rgb = [u8; 3];
+
+pub fn colour(mode: Mode, byte: u8) -> Rgb {
+    match mode {
+        Mode::Bw => [byte, byte, byte],
+        Mode::Four => four(byte),
+        Mode::Detail => {
+            let (r, g, b) = DETAIL[byte as usize];
+            [to_u8(r), to_u8(g), to_u8(b)]
+        }
+        // Where the class of the block is not known, as if it were blank.
+        Mode::Class => class(Class::Blank, byte),
+    }
+}
+
+/// Tint of each `Class`, in the order of its variants, at most 127 so that half a byte
+/// more still fits.
+const TINTS: [Rgb; 5] = [
+    [0, 0, 0],
+    [16, 64, 127],
+    [32, 112, 32],
+    [127, 112, 0],
+    [127, 0, 32],
+];
+
+/// Colour of a byte in a block of `class`, as in `frag_class_2d_140.glsl`:
+/// the tint of the class plus half the byte value.
+pub fn class(class: Class, byte: u8) -> Rgb {
+    let tint = TINTS[class as usize];
+    [tint[0] + byte / 2, tint[1] + byte / 2, tint[2] + byte / 2]
+}
+
+fn four(byte: u8) -> Rgb {
+    match byte {
+        0x00 => [0, 0, 0],
+        0xff => [255, 255, 255],
+        b if (9 <= b && b <= 13) || (32 <= b && b <= 126) => [55, 126, 184],
+        _ => [228, 26, 28],
+    }
+}
+
+/// Colour of a pixel of the XOR diff, as in `frag_xor_2d_140.glsl`.
+pub fn xor(a: u8, b: u8) -> Rgb {
+    if a == b {
+        let c = to_u8(a as f32 / 255f32 / 4f32);
+        [c, c, c]
+    } else {
+        let d = (a as i16 - b as i16).abs() as u8;
+        [255, d, 0]
+    }
+}
+
+/// What a byte is drawn as, rather than in the colour of the mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mark {
+    /// Part of a string.
+    String,
+    /// Part of a search hit.
+    Hit,
+    /// Selected with the mouse.
+    Selection,
+    /// On the outline of a bookmark.
+    Bookmark,
+    /// Between two mappings of a process, where no memory is, or in a hole of a sparse
+    /// file, where no block is.
+    Gap,
+}
+
+/// Colour of a marked byte, as in the `frag_strings`, `frag_hits`, `frag_selection`,
+/// `frag_bookmarks` and `frag_gaps` shaders. Strings go from orange for spaces and
+/// punctuation to yellow for lower case letters, hits from green for zero to cyan for
+/// 0xff, the selection from blue to white, bookmarks are all magenta and gaps dark teal.
+pub fn mark(mark: Mark, byte: u8) -> Rgb {
+    match mark {
+        Mark::String => [255, byte, 0],
+        Mark::Hit => [0, 255, byte],
+        Mark::Selection => [byte, byte, 255],
+        Mark::Bookmark => [255, 0, 255],
+        Mark::Gap => [0, 64, 64],
+    }
+}
+
+fn to_u8(c: f32) -> u8 {
+    (c * 255f32).round() as u8
+}
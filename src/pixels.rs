@@ -0,0 +1,134 @@
+//! Inputs holding raw pictures, such as framebuffer dumps and the bitmaps in firmware:
+//! the bytes are decoded into the colours of pixels rather than coloured by a mode.
+
+use palette::Rgb;
+use raster::Image;
+
+/// How the bytes of a picture make up its pixels.
+#[derive(Debug, Clone, Copy, PartialEq, RustcDecodable)]
+pub enum Pixels {
+    /// One grey level per byte.
+    Gray,
+    Rgb,
+    Bgr,
+    /// Over black, as much as alpha lets through.
+    Rgba,
+    Bgra,
+    /// 5 bits of red, 6 of green and 5 of blue in a little-endian `u16`.
+    Rgb565,
+    /// Planar: a byte of luma per pixel, then a plane of blue and one of red
+    /// chroma, each with a byte per two by two pixels.
+    Yuv420,
+}
+
+impl Pixels {
+    /// Bytes per pixel, the luma plane only for `Yuv420`, so also per row of `width`.
+    pub fn size(&self) -> usize {
+        match *self {
+            Pixels::Gray | Pixels::Yuv420 => 1,
+            Pixels::Rgb565 => 2,
+            Pixels::Rgb | Pixels::Bgr => 3,
+            Pixels::Rgba | Pixels::Bgra => 4,
+        }
+    }
+
+    /// Whether the bytes of a picture are in planes one after the other, so that it
+    /// can only be decoded from its first byte.
+    pub fn planar(&self) -> bool {
+        *self == Pixels::Yuv420
+    }
+
+    /// Bytes of a picture `width`x`height`.
+    pub fn frame_len(&self, width: u32, height: u32) -> u64 {
+        let (width, height) = (width as u64, height as u64);
+        match *self {
+            Pixels::Yuv420 => width * height + 2 * ((width + 1) / 2) * ((height + 1) / 2),
+            _ => width * height * self.size() as u64,
+        }
+    }
+}
+
+/// The picture `width`x`height` at the start of `bytes`, black from where they run out.
+pub fn decode(pixels: Pixels, width: u32, height: u32, bytes: &[u8]) -> Image {
+    let mut image = Image::new(width, height);
+    let size = pixels.size();
+    let byte = |i: usize| *bytes.get(i).unwrap_or(&0);
+    for y in 0..height {
+        for x in 0..width {
+            let i = (y * width + x) as usize;
+            if (i + 1) * size > bytes.len() {
+                return image;
+            }
+            let at = i * size;
+            let c = match pixels {
+                Pixels::Gray => [byte(at); 3],
+                Pixels::Rgb => [byte(at), byte(at + 1), byte(at + 2)],
+                Pixels::Bgr => [byte(at + 2), byte(at + 1), byte(at)],
+                Pixels::Rgba => over_black([byte(at), byte(at + 1), byte(at + 2)], byte(at + 3)),
+                Pixels::Bgra => over_black([byte(at + 2), byte(at + 1), byte(at)], byte(at + 3)),
+                Pixels::Rgb565 => {
+                    let v = byte(at) as u16 | (byte(at + 1) as u16) << 8;
+                    let (r, g, b) = ((v >> 11) as u8, (v >> 5 & 0x3f) as u8, (v & 0x1f) as u8);
+                    [r << 3 | r >> 2, g << 2 | g >> 4, b << 3 | b >> 2]
+                }
+                Pixels::Yuv420 => {
+                    let luma = (width * height) as usize;
+                    let chroma = ((width as usize + 1) / 2) * ((height as usize + 1) / 2);
+                    let j = (y as usize / 2) * ((width as usize + 1) / 2) + x as usize / 2;
+                    // Grey where the chroma planes run out.
+                    let chroma_at = |i: usize| *bytes.get(i).unwrap_or(&128);
+                    yuv(byte(at), chroma_at(luma + j), chroma_at(luma + chroma + j))
+                }
+            };
+            image.set(x, y, c);
+        }
+    }
+    image
+}
+
+fn over_black(c: Rgb, alpha: u8) -> Rgb {
+    let scale = |v: u8| ((v as u32 * alpha as u32 + 127) / 255) as u8;
+    [scale(c[0]), scale(c[1]), scale(c[2])]
+}
+
+/// Full range BT.601, as JPEG has it.
+fn yuv(y: u8, u: u8, v: u8) -> Rgb {
+    let (y, u, v) = (y as f32, u as f32 - 128f32, v as f32 - 128f32);
+    let clamp = |c: f32| c.round().max(0f32).min(255f32) as u8;
+    [clamp(y + 1.402 * v), clamp(y - 0.344136 * u - 0.714136 * v), clamp(y + 1.772 * u)]
+}
+
+/// Grey level of each pixel of `image`, for the marks drawn over it.
+pub fn luma(image: &Image) -> Vec<u8> {
+    image.pixels.iter()
+        .map(|c| ((77 * c[0] as u32 + 150 * c[1] as u32 + 29 * c[2] as u32) >> 8) as u8)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, Pixels};
+
+    fn one(pixels: Pixels, bytes: &[u8]) -> [u8; 3] {
+        decode(pixels, 1, 1, bytes).pixels[0]
+    }
+
+    #[test]
+    fn packed() {
+        assert_eq!(one(Pixels::Gray, &[7]), [7, 7, 7]);
+        assert_eq!(one(Pixels::Bgr, &[1, 2, 3]), [3, 2, 1]);
+        assert_eq!(one(Pixels::Rgba, &[255, 128, 0, 128]), [128, 64, 0]);
+        assert_eq!(one(Pixels::Rgb565, &[0x1f, 0xf8]), [255, 0, 255]);
+        assert_eq!(one(Pixels::Rgb565, &[0xe0, 0x07]), [0, 255, 0]);
+        // Bytes short of a whole pixel are left black.
+        assert_eq!(decode(Pixels::Rgb, 2, 1, &[9, 9, 9, 9]).pixels, vec![[9, 9, 9], [0, 0, 0]]);
+    }
+
+    #[test]
+    fn planes() {
+        assert_eq!(one(Pixels::Yuv420, &[128, 128, 128]), [128, 128, 128]);
+        assert_eq!(one(Pixels::Yuv420, &[76, 85, 255]), [254, 0, 0]);
+        assert_eq!(Pixels::Yuv420.frame_len(5, 3), 15 + 2 * 3 * 2);
+        assert!(Pixels::Yuv420.planar() && !Pixels::Rgb.planar());
+    }
+}
@@ -0,0 +1,259 @@
+//! The memory of a running process on Linux: the mappings that `/proc/<pid>/maps` lists,
+//! read from `/proc/<pid>/mem`. They are shown one after the other in address order,
+//! with `GAP` bytes between two wherever unmapped addresses come between them, so that
+//! the gaps show without the billions of unmapped bytes around them.
+
+use std::cmp;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+
+use error::Error;
+
+/// Bytes shown between two mappings that don't follow one another.
+pub const GAP: u64 = 4096;
+
+/// Addresses of one mapping.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mapping {
+    /// From `start` up to `end`.
+    pub start: u64,
+    pub end: u64,
+    /// As `maps` has them, such as `r-xp`.
+    pub perms: String,
+    /// The file mapped, or the likes of `[heap]` and `[stack]`; empty when anonymous.
+    pub path: String,
+    /// Where its first byte is shown.
+    pub offset: u64,
+}
+
+impl Mapping {
+    pub fn len(&self) -> u64 {
+        self.end - self.start
+    }
+
+    /// What it is called over the bytes: the file name of a library, `[heap]`, `[stack]`
+    /// or `anon`, with its address and permissions.
+    pub fn label(&self) -> String {
+        let name = match self.path.rfind('/') {
+            Some(slash) if !self.path.starts_with('[') => &self.path[slash + 1..],
+            _ if self.path.is_empty() => "anon",
+            _ => &self.path,
+        };
+        format!("{} {:x} {}", name, self.start, self.perms)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Process {
+    pub pid: u32,
+    /// `/proc/<pid>/mem`.
+    pub path: String,
+    /// The readable mappings, by address.
+    pub mappings: Vec<Mapping>,
+}
+
+/// The readable mappings that `text`, as `/proc/<pid>/maps` has it, lists, laid out one
+/// after the other. `[vvar]` and `[vsyscall]` can't be read through `mem`, so are left out.
+pub fn parse(text: &str) -> Vec<Mapping> {
+    let mut mappings: Vec<Mapping> = vec![];
+    for line in text.lines() {
+        let fields: Vec<&str> = line.splitn(6, ' ').collect();
+        if fields.len() < 5 {
+            continue;
+        }
+        let range: Vec<&str> = fields[0].splitn(2, '-').collect();
+        let (start, end) = match (range.get(0).map(|s| u64::from_str_radix(s, 16)),
+                                  range.get(1).map(|s| u64::from_str_radix(s, 16))) {
+            (Some(Ok(start)), Some(Ok(end))) if start < end => (start, end),
+            _ => continue,
+        };
+        let path = fields.get(5).map_or("", |path| path.trim());
+        if !fields[1].starts_with('r') || path == "[vvar]" || path == "[vsyscall]" {
+            continue;
+        }
+        let offset = match mappings.last() {
+            Some(last) if last.end == start => last.offset + last.len(),
+            Some(last) => last.offset + last.len() + GAP,
+            None => 0,
+        };
+        mappings.push(Mapping {
+            start: start,
+            end: end,
+            perms: fields[1].to_owned(),
+            path: path.to_owned(),
+            offset: offset,
+        });
+    }
+    mappings
+}
+
+impl Process {
+    /// The mappings of process `pid`, as long as its memory can be read.
+    pub fn new(pid: u32) -> Result<Process, Error> {
+        let maps = format!("/proc/{}/maps", pid);
+        let mut text = String::new();
+        try!(File::open(&maps).and_then(|mut f| f.read_to_string(&mut text))
+             .map_err(|e| Error::Input(maps.clone(), e)));
+        let path = format!("/proc/{}/mem", pid);
+        // Not allowed to trace the process, there is nothing to show.
+        try!(File::open(&path).map_err(|e| Error::Input(path.clone(), e)));
+        let mappings = parse(&text);
+        debug!("{:?} mappings of process {:?}", mappings.len(), pid);
+        Ok(Process{pid: pid, path: path, mappings: mappings})
+    }
+
+    /// Bytes shown, gaps included.
+    pub fn len(&self) -> u64 {
+        self.mappings.last().map_or(0, |last| last.offset + last.len())
+    }
+
+    /// Where the gaps between mappings are shown, and how many bytes each is.
+    pub fn gaps(&self) -> Vec<(u64, u64)> {
+        self.mappings.windows(2)
+            .filter(|pair| pair[0].end != pair[1].start)
+            .map(|pair| (pair[0].offset + pair[0].len(), GAP))
+            .collect()
+    }
+
+    /// At most `len` bytes shown from `offset`. Gaps, and mappings that turn out not to
+    /// be readable such as guard pages, read as zeros.
+    pub fn read(&self, offset: u64, len: u64) -> io::Result<Vec<u8>> {
+        let mut mem = try!(File::open(&self.path));
+        read_at(&mut mem, &self.mappings, offset, len)
+    }
+
+    /// The bytes shown from `offset` on.
+    pub fn open_at(&self, offset: u64) -> io::Result<Reader> {
+        Ok(Reader{mem: try!(File::open(&self.path)), mappings: self.mappings.clone(), offset: offset})
+    }
+}
+
+fn read_at(mem: &mut File, mappings: &[Mapping], offset: u64, len: u64) -> io::Result<Vec<u8>> {
+    let total = mappings.last().map_or(0, |last| last.offset + last.len());
+    let end = cmp::min(offset.saturating_add(len), total);
+    if offset >= end {
+        return Ok(vec![]);
+    }
+    let mut buffer = vec![0; (end - offset) as usize];
+    for mapping in mappings {
+        let from = cmp::max(offset, mapping.offset);
+        let to = cmp::min(end, mapping.offset + mapping.len());
+        if from >= to {
+            continue;
+        }
+        let slice = &mut buffer[(from - offset) as usize..(to - offset) as usize];
+        let read = mem.seek(SeekFrom::Start(mapping.start + from - mapping.offset))
+            .and_then(|_| mem.read_exact(slice));
+        if let Err(e) = read {
+            debug!("cannot read {:x}-{:x}: {}", mapping.start, mapping.end, e);
+            for b in slice.iter_mut() {
+                *b = 0;
+            }
+        }
+    }
+    Ok(buffer)
+}
+
+/// Reads the bytes shown one after the other, as `Process::open_at` starts it.
+pub struct Reader {
+    mem: File,
+    mappings: Vec<Mapping>,
+    offset: u64,
+}
+
+impl Read for Reader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let bytes = try!(read_at(&mut self.mem, &self.mappings, self.offset, buf.len() as u64));
+        buf[..bytes.len()].copy_from_slice(&bytes);
+        self.offset += bytes.len() as u64;
+        Ok(bytes.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+    use std::process::{Child, Command};
+    use std::thread;
+    use std::time::Duration;
+
+    use fixtures::Script;
+    use palette::{self, Mark, Mode};
+    use render::{self, Input, Renderer, View};
+    use source::Source;
+    use super::{parse, Process, GAP};
+
+    #[test]
+    fn maps() {
+        let maps = "\
+55d0c0a00000-55d0c0a02000 r--p 00000000 fd:01 1234                       /usr/bin/sleep
+55d0c0a02000-55d0c0a06000 r-xp 00002000 fd:01 1234                       /usr/bin/sleep
+55d0c1c00000-55d0c1c21000 rw-p 00000000 00:00 0                          [heap]
+7f2a10000000-7f2a10001000 ---p 00000000 00:00 0 
+7f2a10001000-7f2a10003000 rw-p 00000000 00:00 0 
+7fff5c000000-7fff5c021000 rw-p 00000000 00:00 0                          [stack]
+7fff5c1fe000-7fff5c202000 r--p 00000000 00:00 0                          [vvar]
+ffffffffff600000-ffffffffff601000 --xp 00000000 00:00 0                  [vsyscall]
+";
+        let mappings = parse(maps);
+        let labels: Vec<String> = mappings.iter().map(|mapping| mapping.label()).collect();
+        assert_eq!(labels, vec!["sleep 55d0c0a00000 r--p", "sleep 55d0c0a02000 r-xp", "[heap] 55d0c1c00000 rw-p",
+                                "anon 7f2a10001000 rw-p", "[stack] 7fff5c000000 rw-p"]);
+        let offsets: Vec<u64> = mappings.iter().map(|mapping| mapping.offset).collect();
+        assert_eq!(offsets, vec![0, 0x2000, 0x6000 + GAP, 0x27000 + 2 * GAP, 0x29000 + 3 * GAP]);
+        let process = Process{pid: 1, path: "/proc/1/mem".to_owned(), mappings: mappings};
+        assert_eq!(process.len(), 0x4a000 + 3 * GAP);
+        assert_eq!(process.gaps(), vec![(0x6000, GAP), (0x27000 + GAP, GAP), (0x29000 + 2 * GAP, GAP)]);
+    }
+
+    /// A child process, killed when the test is done with it, passed or not.
+    struct Sleeping(Child);
+
+    impl Drop for Sleeping {
+        fn drop(&mut self) {
+            let _ = self.0.kill();
+            let _ = self.0.wait();
+        }
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn memory() {
+        let child = Sleeping(Command::new("sleep").arg("60").spawn().unwrap());
+        // Until it has exec'd, the child maps little of anything.
+        let mut process = Process::new(child.0.id()).unwrap();
+        for _ in 0..100 {
+            if process.mappings.iter().any(|mapping| mapping.path.ends_with("sleep")) {
+                break;
+            }
+            thread::sleep(Duration::from_millis(50));
+            process = Process::new(child.0.id()).unwrap();
+        }
+        assert!(process.mappings.iter().any(|mapping| mapping.path == "[stack]"));
+        let source = Source::process(&process);
+        assert_eq!(source.len().unwrap(), process.len());
+        // The executable comes first, starting with its ELF header.
+        assert_eq!(source.read(0, 4).unwrap(), b"\x7fELF");
+        let mut start = vec![];
+        source.open().unwrap().take(64 * 1024).read_to_end(&mut start).unwrap();
+        assert_eq!(start, source.read(0, 64 * 1024).unwrap());
+        let (gap, len) = process.gaps()[0];
+        assert_eq!(source.read(gap, len).unwrap(), vec![0; len as usize]);
+
+        // Rows of 128 bytes, left of the list of mappings, scrolled to show the first gap
+        // 16 rows down, clear of the label of the mapping before it: all of its rows drawn
+        // as a gap and those around it not.
+        let mut view = View::process(&process, Mode::Four).unwrap();
+        view.stride = Some(128);
+        let above = ::std::cmp::min(16, gap / 128);
+        let mut script = Script::new(256, 256, vec![vec![Input::Scroll((gap / 128 - above) as i64)]]);
+        render::run(&mut script, &view).unwrap();
+        let frame = script.cpu.frame().unwrap();
+        let gap_colour = palette::mark(Mark::Gap, 0);
+        let is_gap = |y: u64| frame.pixels[y as usize * 256..][..128].iter().all(|&c| c == gap_colour);
+        let rows = len / 128;
+        assert!((above..above + rows).all(&is_gap));
+        assert!(above == 0 || !is_gap(above - 1));
+        assert!(!is_gap(above + rows));
+    }
+}
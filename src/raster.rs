@@ -0,0 +1,208 @@
+//! Rasterization on the CPU, for outputs that have no OpenGL context.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Read};
+use std::path::Path;
+
+use png::{self, HasParameters};
+
+use classify::Class;
+use font;
+use palette::{self, Mark, Mode, Rgb};
+
+/// An RGB picture, row-major from the top left pixel.
+#[derive(Clone)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<Rgb>,
+}
+
+impl Image {
+    pub fn new(width: u32, height: u32) -> Image {
+        Image {
+            width: width,
+            height: height,
+            pixels: vec![[0, 0, 0]; (width * height) as usize],
+        }
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> Rgb {
+        self.pixels[(y * self.width + x) as usize]
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, c: Rgb) {
+        self.pixels[(y * self.width + x) as usize] = c;
+    }
+
+    /// Pixels as consecutive R, G, B bytes.
+    pub fn rgb(&self) -> Vec<u8> {
+        let mut rgb = Vec::with_capacity(3 * self.pixels.len());
+        for c in &self.pixels {
+            rgb.extend_from_slice(c);
+        }
+        rgb
+    }
+
+    pub fn save_png(&self, path: &Path) -> io::Result<()> {
+        let file = try!(File::create(path));
+        let mut encoder = png::Encoder::new(BufWriter::new(file), self.width, self.height);
+        encoder.set(png::ColorType::RGB).set(png::BitDepth::Eight);
+        let mut writer = try!(encoder.write_header());
+        try!(writer.write_image_data(&self.rgb()));
+        Ok(())
+    }
+
+    /// Paints a rectangle, clipping what doesn't fit.
+    pub fn fill(&mut self, left: u32, top: u32, width: u32, height: u32, c: Rgb) {
+        for y in top..::std::cmp::min(top + height, self.height) {
+            for x in left..::std::cmp::min(left + width, self.width) {
+                self.set(x, y, c);
+            }
+        }
+    }
+
+    /// Writes `text` on one line from `(left, top)` in the `font` glyphs, clipping what doesn't fit.
+    pub fn text(&mut self, left: u32, top: u32, text: &str, c: Rgb) {
+        for (i, ch) in text.chars().enumerate() {
+            let x0 = left + i as u32 * font::WIDTH;
+            if x0 >= self.width {
+                break;
+            }
+            for (dy, bits) in font::glyph(ch).iter().enumerate() {
+                for dx in 0..5 {
+                    let (x, y) = (x0 + dx, top + dy as u32);
+                    if bits & (0x10 >> dx) != 0 && x < self.width && y < self.height {
+                        self.set(x, y, c);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Copies `other` in with its top left corner at `(left, top)`, clipping what doesn't fit.
+    pub fn blit(&mut self, other: &Image, left: u32, top: u32) {
+        for y in 0..other.height {
+            for x in 0..other.width {
+                if left + x < self.width && top + y < self.height {
+                    self.set(left + x, top + y, other.get(x, y));
+                }
+            }
+        }
+    }
+}
+
+/// Coordinates of the pixels that differ between two pictures of the same size.
+pub fn mismatches(expected: &Image, actual: &Image) -> Vec<(u32, u32)> {
+    let mut found = vec![];
+    for y in 0..expected.height {
+        for x in 0..expected.width {
+            if expected.get(x, y) != actual.get(x, y) {
+                found.push((x, y));
+            }
+        }
+    }
+    found
+}
+
+/// Same layout as `bytes_to_texture2d` drawn by the `mode` shader:
+/// `width` bytes per row, pixels past the end of `buffer` taken as zero.
+pub fn bytes(mode: Mode, width: u32, height: u32, buffer: &[u8]) -> Image {
+    let mut image = Image::new(width, height);
+    for (i, pixel) in image.pixels.iter_mut().enumerate() {
+        *pixel = palette::colour(mode, *buffer.get(i).unwrap_or(&0));
+    }
+    image
+}
+
+/// Recolours the bytes of `buffer` in `ranges` as `mark`, in the `bytes` layout of a pane
+/// `width` pixels wide whose top left corner is at `left`, `top`.
+pub fn highlight(image: &mut Image, left: u32, top: u32, width: u32, buffer: &[u8], ranges: &[(usize, usize)],
+                 mark: Mark) {
+    let height = image.height;
+    for &(start, end) in ranges {
+        for i in start..end {
+            let (x, y) = (i as u32 % width, top + i as u32 / width);
+            if y < height {
+                image.set(left + x, y, palette::mark(mark, *buffer.get(i).unwrap_or(&0)));
+            }
+        }
+    }
+}
+
+/// Recolours the bytes of `buffer` by the class of their block, `classes` having one
+/// for each byte, in the `bytes` layout of a pane `width` pixels wide from `left`, `top`.
+pub fn classes(image: &mut Image, left: u32, top: u32, width: u32, buffer: &[u8], classes: &[Class]) {
+    for (i, (&byte, &class)) in buffer.iter().zip(classes).enumerate() {
+        let (x, y) = (i as u32 % width, top + i as u32 / width);
+        if y < image.height {
+            image.set(left + x, y, palette::class(class, byte));
+        }
+    }
+}
+
+/// Width of each of the two panes of `side_by_side`.
+pub fn pane_width(width: u32) -> u32 {
+    width.saturating_sub(1) / 2
+}
+
+/// Two `bytes` layouts next to each other with a one pixel gap, as `minrs diff` shows them.
+pub fn side_by_side(mode: Mode, width: u32, height: u32, a: &[u8], b: &[u8]) -> Image {
+    let pane_width = pane_width(width);
+    let mut image = Image::new(width, height);
+    image.blit(&bytes(mode, pane_width, height, a), 0, 0);
+    image.blit(&bytes(mode, pane_width, height, b), width - pane_width, 0);
+    image
+}
+
+/// Same as `frag_xor_2d_140.glsl` over two `bytes_to_texture2d` layouts.
+pub fn xor(width: u32, height: u32, a: &[u8], b: &[u8]) -> Image {
+    let mut image = Image::new(width, height);
+    for (i, pixel) in image.pixels.iter_mut().enumerate() {
+        *pixel = palette::xor(*a.get(i).unwrap_or(&0), *b.get(i).unwrap_or(&0));
+    }
+    image
+}
+
+/// All `len` bytes of `reader` in `width`x`height` pixels: when they don't fit
+/// one per pixel, each pixel gets the average colour of the run of bytes it covers.
+pub fn overview<R: Read>(mode: Mode, width: u32, height: u32, mut reader: R, len: u64) -> io::Result<Image> {
+    let n = (width * height) as u64;
+    if len <= n {
+        let mut buffer = vec![];
+        try!(reader.take(n).read_to_end(&mut buffer));
+        return Ok(bytes(mode, width, height, &buffer));
+    }
+
+    let mut image = Image::new(width, height);
+    let mut pixel = 0;
+    let mut end = len / n;
+    let mut offset = 0u64;
+    let mut sums = [0u64; 3];
+    let mut buffer = [0u8; 64 * 1024];
+    while pixel < n {
+        let read = try!(reader.read(&mut buffer));
+        if read == 0 {
+            break;
+        }
+        for &byte in &buffer[..read] {
+            let c = palette::colour(mode, byte);
+            for i in 0..3 {
+                sums[i] += c[i] as u64;
+            }
+            offset += 1;
+            if offset == end {
+                let count = end - pixel * len / n;
+                image.pixels[pixel as usize] =
+                    [(sums[0] / count) as u8, (sums[1] / count) as u8, (sums[2] / count) as u8];
+                sums = [0; 3];
+                pixel += 1;
+                end = (pixel + 1) * len / n;
+                if pixel == n {
+                    break;
+                }
+            }
+        }
+    }
+    Ok(image)
+}
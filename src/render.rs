@@ -0,0 +1,1301 @@
+//! The render loop, and what it needs from whatever does the drawing:
+//! OpenGL in a window (`gl::Gl`) or the CPU alone (`cpu::Cpu`).
+
+use std::cmp;
+use std::io;
+use std::path::Path;
+
+use archive::{self, Member};
+use bits::{self, Order};
+use bookmarks::{Bookmark, Project};
+use carve;
+use classify::{self, Class};
+use diff;
+use entropy;
+use error::Error;
+use font;
+use minimap::{self, Minimap};
+use mosaic::Mosaic;
+use palette::{Mode, Mark, Rgb};
+use pixels::{self, Pixels};
+use process::Process;
+use raster::{self, Image};
+use samples::{self, Format};
+use search::{Hit, Pattern};
+use source::Source;
+use sparse;
+use stride;
+use strings::{self, Run};
+
+/// Rows scrolled by the arrow keys and by one notch of the mouse wheel.
+pub const SCROLL_ROWS: i64 = 16;
+
+/// Characters across the list of strings, when the window is wide enough.
+const LIST_COLUMNS: u32 = 48;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Layout {
+    /// One input, one pane as wide as the picture.
+    Single,
+    /// Two inputs in panes of `raster::pane_width`, as `minrs diff` shows them.
+    SideBySide,
+    /// Two inputs in one pane where the bytes that differ light up.
+    Xor,
+    /// Any number of inputs in panes of the same size, a pixel apart across and each
+    /// named on a line under it.
+    Grid { columns: u32, rows: u32 },
+}
+
+impl Layout {
+    /// The grid with room for `count` panes, as many across as down or one more.
+    pub fn grid(count: usize) -> Layout {
+        let count = count as u32;
+        let columns = (1..).find(|&columns| columns * columns >= count).unwrap();
+        Layout::Grid{columns: columns, rows: (count + columns - 1) / columns}
+    }
+
+    /// Bytes per row of each pane in a picture `width` pixels wide.
+    pub fn row_len(&self, width: u32) -> u32 {
+        match *self {
+            Layout::SideBySide => raster::pane_width(width),
+            Layout::Grid{columns, ..} => width.saturating_sub(columns - 1) / columns,
+            Layout::Single | Layout::Xor => width,
+        }
+    }
+
+    /// Rows of each pane in a picture `height` pixels high.
+    pub fn pane_height(&self, height: u32) -> u32 {
+        match *self {
+            Layout::Grid{rows, ..} => (height / rows).saturating_sub(font::HEIGHT),
+            Layout::Single | Layout::SideBySide | Layout::Xor => height,
+        }
+    }
+}
+
+/// A picture drawn over the panes, such as the list beside them.
+pub struct Panel {
+    pub left: u32,
+    pub top: u32,
+    pub image: Image,
+}
+
+/// Bytes of one buffer to draw in the colour of a `Mark` rather than of the mode.
+pub struct Marks {
+    pub buffer: usize,
+    pub mark: Mark,
+    /// Ranges of the buffer, in the order drawn: later ones go over earlier ones.
+    pub ranges: Vec<(usize, usize)>,
+}
+
+/// Entropy down the rows of a strip as high as the window, drawn by its own shader.
+pub struct Graph {
+    pub left: u32,
+    /// How many pixels right the line is on each row, as `entropy::levels` makes them.
+    pub levels: Vec<u8>,
+    /// First and last rows showing the bytes on screen.
+    pub visible: (u32, u32),
+}
+
+/// A screenful of bytes of each input, `row_len` bytes per row from the top left pixel.
+pub struct Scene {
+    pub layout: Layout,
+    pub mode: Mode,
+    /// Pixels across the panes, from the left edge.
+    pub width: u32,
+    /// Pixels down the panes, and their labels, from the top.
+    pub height: u32,
+    pub buffers: Vec<Vec<u8>>,
+    /// In `Mode::Class`, the class of the block of each byte of each buffer.
+    pub classes: Vec<Vec<Class>>,
+    pub marks: Vec<Marks>,
+    pub panels: Vec<Panel>,
+    pub graph: Option<Graph>,
+    /// Bytes per row when narrower than the panes, to line records up in columns.
+    pub stride: Option<u32>,
+}
+
+impl Scene {
+    pub fn row_len(&self) -> u32 {
+        let row_len = self.layout.row_len(self.width);
+        self.stride.map_or(row_len, |stride| cmp::min(stride, row_len))
+    }
+
+    pub fn pane_height(&self) -> u32 {
+        self.layout.pane_height(self.height)
+    }
+
+    /// The buffer of the pane at `x`, `y` from the top left, and the index in it of the
+    /// byte drawn there, if any.
+    pub fn byte_at(&self, x: u32, y: u32) -> Option<(usize, usize)> {
+        let row_len = self.row_len();
+        let (pane, column, y) = match self.layout {
+            Layout::SideBySide if x >= self.pane_left(1) => (1, x - self.pane_left(1), y),
+            Layout::Grid{columns, rows} => {
+                let (across, down) = (self.layout.row_len(self.width) + 1, self.height / rows);
+                let i = (y / down * columns + x / across) as usize;
+                if x / across >= columns || i >= self.buffers.len() {
+                    return None;
+                }
+                (i, x % across, y % down)
+            }
+            _ => (0, x, y),
+        };
+        if column >= row_len || y >= self.pane_height() {
+            return None;
+        }
+        Some((pane, y as usize * row_len as usize + column as usize))
+    }
+
+    /// Left edge of the pane showing buffer `i`.
+    pub fn pane_left(&self, i: usize) -> u32 {
+        match (self.layout, i) {
+            (Layout::SideBySide, 1) => self.width - self.layout.row_len(self.width),
+            (Layout::Grid{columns, ..}, _) => i as u32 % columns * (self.layout.row_len(self.width) + 1),
+            _ => 0,
+        }
+    }
+
+    /// Top edge of the pane showing buffer `i`.
+    pub fn pane_top(&self, i: usize) -> u32 {
+        match self.layout {
+            Layout::Grid{columns, rows} => i as u32 / columns * (self.height / rows),
+            _ => 0,
+        }
+    }
+
+    /// Pixel for pixel what the shaders draw for this scene.
+    pub fn rasterize(&self, width: u32, height: u32) -> Image {
+        let row_len = self.row_len();
+        let panes = match self.layout {
+            Layout::Single => raster::bytes(self.mode, row_len, height, &self.buffers[0]),
+            Layout::SideBySide if self.stride.is_none() =>
+                raster::side_by_side(self.mode, self.width, height, &self.buffers[0], &self.buffers[1]),
+            Layout::SideBySide | Layout::Grid{..} => {
+                let mut panes = Image::new(self.width, height);
+                for (i, buffer) in self.buffers.iter().enumerate() {
+                    let pane = raster::bytes(self.mode, row_len, self.pane_height(), buffer);
+                    panes.blit(&pane, self.pane_left(i), self.pane_top(i));
+                }
+                panes
+            }
+            Layout::Xor => raster::xor(row_len, height, &self.buffers[0], &self.buffers[1]),
+        };
+        if panes.width == width && self.classes.is_empty() && self.marks.is_empty() &&
+            self.panels.is_empty() && self.graph.is_none() {
+            return panes;
+        }
+        let mut image = Image::new(width, height);
+        image.blit(&panes, 0, 0);
+        for (i, classes) in self.classes.iter().enumerate() {
+            raster::classes(&mut image, self.pane_left(i), self.pane_top(i), row_len, &self.buffers[i], classes);
+        }
+        for marks in &self.marks {
+            raster::highlight(&mut image, self.pane_left(marks.buffer), self.pane_top(marks.buffer), row_len,
+                              &self.buffers[marks.buffer], &marks.ranges, marks.mark);
+        }
+        for panel in &self.panels {
+            image.blit(&panel.image, panel.left, panel.top);
+        }
+        if let Some(ref graph) = self.graph {
+            image.blit(&entropy::graph(&graph.levels, graph.visible), graph.left, 0);
+        }
+        image
+    }
+}
+
+/// What the user asked for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Input {
+    Quit,
+    /// Move this many rows down, or up when negative.
+    Scroll(i64),
+    /// Move this many screens down, or up when negative.
+    Page(i64),
+    Home,
+    /// A click at this pixel from the top left.
+    Click(u32, u32),
+    /// The mouse moved to this pixel with the button still down since a `Click`.
+    Drag(u32, u32),
+    /// The button of the last `Click` went up.
+    Release,
+    /// Select the string this many down the list, or up when negative.
+    Select(i64),
+    /// A character typed.
+    Char(char),
+    Backspace,
+    Enter,
+    /// Quits, or leaves the prompt or the selection.
+    Escape,
+}
+
+pub trait Renderer {
+    /// Size in pixels of the pictures `draw` makes.
+    fn size(&self) -> (u32, u32);
+
+    /// Takes the bytes to draw from now on.
+    fn load(&mut self, scene: &Scene) -> Result<(), Error>;
+
+    /// Draws what was last loaded.
+    fn draw(&mut self) -> Result<(), Error>;
+
+    /// What the user asked for since the last call.
+    fn input(&mut self) -> Vec<Input>;
+
+    /// The picture last drawn, read back.
+    fn frame(&self) -> Result<Image, Error>;
+}
+
+/// The inputs to show and what to do with the first frame.
+pub struct View<'a> {
+    sources: Vec<Source<'a>>,
+    layout: Layout,
+    mode: Mode,
+    len: u64,
+    /// Block size and entropy of each block of the first input, unless not asked for.
+    entropy: Option<(usize, Vec<f64>)>,
+    /// Strings found in each input, by offset; none unless asked for.
+    strings: Vec<Vec<Run>>,
+    /// Searched for before the first frame.
+    search: Option<(String, Pattern)>,
+    /// How bytes make up the samples drawn one per pixel, and the values from black to white.
+    format: Format,
+    range: (f64, f64),
+    /// Decode the bytes as pictures as wide as the rows, rather than colour them by `mode`.
+    pub pixels: Option<Pixels>,
+    /// Rows of each frame of planar `pixels`, rather than as many as fit down the panes.
+    pub frame_height: Option<u32>,
+    /// Draw each bit as a pixel, rather than each byte.
+    pub bits: Option<Order>,
+    /// Save the first frame as a PNG file there, then stop.
+    pub png: Option<&'a str>,
+    /// Fail unless the first frame is exactly the CPU rasterization of the same bytes.
+    pub check: bool,
+    /// Show the whole of the first input along the right edge.
+    pub minimap: bool,
+    /// Bytes per block classified in `Mode::Class`.
+    pub block: usize,
+    /// Bytes per row to start with, rather than as many as fit across the panes.
+    pub stride: Option<u32>,
+    /// Start with the stride `stride::detect` finds at the start of the first input.
+    pub auto_stride: bool,
+}
+
+impl<'a> View<'a> {
+    /// Checks there is something to show before any window opens.
+    pub fn new(paths: Vec<&'a str>, layout: Layout, mode: Mode) -> Result<View<'a>, Error> {
+        View::of(paths.into_iter().map(Source::raw).collect(), layout, mode)
+    }
+
+    /// The memory of `process`, its mappings labelled.
+    pub fn process(process: &'a Process, mode: Mode) -> Result<View<'a>, Error> {
+        View::of(vec![Source::process(process)], Layout::Single, mode)
+    }
+
+    fn of(mut sources: Vec<Source<'a>>, layout: Layout, mode: Mode) -> Result<View<'a>, Error> {
+        let len = try!(total_len(&sources));
+        for source in sources.iter_mut().filter(|source| source.process.is_none()) {
+            source.holes = try!(sparse::holes(source.path, try!(source.len())));
+            if !source.holes.is_empty() {
+                let bytes: u64 = source.holes.iter().map(|&(_, len)| len).sum();
+                debug!("{:?} holes of {:?} bytes in {:?}", source.holes.len(), bytes, source.path);
+            }
+        }
+        Ok(View {
+            sources: sources,
+            layout: layout,
+            mode: mode,
+            len: len,
+            entropy: None,
+            strings: vec![],
+            search: None,
+            format: Format::U8,
+            range: (0f64, 255f64),
+            pixels: None,
+            frame_height: None,
+            bits: None,
+            png: None,
+            check: false,
+            minimap: false,
+            block: classify::BLOCK,
+            stride: None,
+            auto_stride: false,
+        })
+    }
+
+    /// The files in the first input when it is a ZIP or TAR archive.
+    pub fn members(&self) -> Result<Option<Vec<Member>>, Error> {
+        archive::members(&self.sources[0])
+    }
+
+    /// Shows `member` of the archive that the first input is rather than all of it.
+    pub fn open_member(&mut self, member: Member) -> Result<(), Error> {
+        debug!("showing {:?} of {:?}", member.name, self.sources[0].path);
+        self.sources[0] = self.sources[0].member(member);
+        self.len = try!(total_len(&self.sources));
+        Ok(())
+    }
+
+    /// Shows what the compressed inputs decompress to rather than their own bytes.
+    pub fn decompress(&mut self) -> Result<(), Error> {
+        for source in self.sources.iter_mut().filter(|source| source.process.is_none()) {
+            source.codec = try!(Source::sniff(source.path)).codec;
+            if let Some(codec) = source.codec {
+                debug!("decompressing {:?} as {:?}", source.path, codec);
+            }
+        }
+        self.len = try!(total_len(&self.sources));
+        Ok(())
+    }
+
+    /// Highlights strings of at least `min_len` characters in every input
+    /// and lists those of the first one beside the panes.
+    pub fn find_strings(&mut self, min_len: usize) -> Result<(), Error> {
+        self.strings.clear();
+        for source in &self.sources {
+            let runs = try!(strings::scan(try!(source.open()), min_len).map_err(|e| source.error(e)));
+            debug!("{:?} strings in {:?}", runs.len(), source.path);
+            self.strings.push(runs);
+        }
+        Ok(())
+    }
+
+    /// Graphs the entropy of each `block` bytes of the first input beside the panes.
+    pub fn find_entropy(&mut self, block: usize) -> Result<(), Error> {
+        let source = &self.sources[0];
+        let series = try!(entropy::blocks(try!(source.open()), block).map_err(|e| source.error(e)));
+        debug!("entropy of {:?} blocks of {:?}", series.len(), source.path);
+        self.entropy = Some((block, series));
+        Ok(())
+    }
+
+    /// Draws each sample of `format` as one pixel, scaled from the lowest to the highest
+    /// value in all inputs.
+    pub fn decode(&mut self, format: Format) -> Result<(), Error> {
+        if self.len < format.size() as u64 {
+            return Err(Error::Empty(self.sources[0].path.to_owned()));
+        }
+        let mut range: Option<(f64, f64)> = None;
+        for source in &self.sources {
+            let found = try!(samples::range(format, try!(source.open())).map_err(|e| source.error(e)));
+            range = match (range, found) {
+                (Some((low, high)), Some((l, h))) => Some((low.min(l), high.max(h))),
+                (range, found) => range.or(found),
+            };
+        }
+        self.format = format;
+        self.range = range.unwrap_or((0f64, 0f64));
+        debug!("{:?} samples from {:?} to {:?}", format, self.range.0, self.range.1);
+        Ok(())
+    }
+
+    /// Highlights the hits of `pattern`, as `search::Pattern::parse` reads it,
+    /// and starts at the first one.
+    pub fn search(&mut self, pattern: &str) -> Result<(), Error> {
+        let compiled = try!(Pattern::parse(pattern).map_err(|e| Error::Pattern(pattern.to_owned(), e)));
+        self.search = Some((pattern.to_owned(), compiled));
+        Ok(())
+    }
+}
+
+/// Bytes in the longest of `sources`, as long as there are some.
+fn total_len(sources: &[Source]) -> Result<u64, Error> {
+    let mut len = 0;
+    for source in sources {
+        len = cmp::max(len, try!(source.len()));
+    }
+    // Comparing against an empty file is fine, as long as there is something to see.
+    if len == 0 {
+        return Err(Error::Empty(sources[0].path.to_owned()));
+    }
+    Ok(len)
+}
+
+/// Hits of `pattern` in each of `sources`.
+fn find_all(sources: &[Source], pattern: &Pattern) -> Result<Vec<Vec<Hit>>, Error> {
+    let mut hits = vec![];
+    for source in sources {
+        hits.push(try!(pattern.find(try!(source.open())).map_err(|e| source.error(e))));
+    }
+    Ok(hits)
+}
+
+fn found(pattern: &str, hits: &[Hit]) -> String {
+    match hits.len() {
+        0 => format!("no hits for {}", pattern),
+        n if n == ::search::MAX_HITS => format!("first {} hits for {}", n, pattern),
+        n => format!("{} hits for {}", n, pattern),
+    }
+}
+
+/// Index of the first of the `(offset, len)` spans, sorted by offset,
+/// that ends past `offset`: the first one on screen from there.
+fn first_from<I: Iterator<Item=(u64, u64)>>(spans: I, offset: u64) -> usize {
+    let mut i = 0;
+    for (start, len) in spans {
+        if start + len > offset {
+            break;
+        }
+        i += 1;
+    }
+    i
+}
+
+/// The parts of the `(offset, len)` spans, sorted by offset, within the `len` bytes
+/// from `offset`, relative to `offset`.
+fn within<I: Iterator<Item=(u64, u64)>>(spans: I, offset: u64, len: u64) -> Vec<(usize, usize)> {
+    let end = offset + len;
+    spans.take_while(|&(start, _)| start < end)
+        .filter(|&(start, len)| start + len > offset)
+        .map(|(start, len)| ((cmp::max(start, offset) - offset) as usize,
+                             (cmp::min(start + len, end) - offset) as usize))
+        .collect()
+}
+
+/// The entry `n` on from `current`, or with nothing current from `first`,
+/// the first one on screen, among `count` entries.
+fn step(current: Option<usize>, first: usize, count: usize, n: i64) -> Option<usize> {
+    if count == 0 {
+        return None;
+    }
+    // With nothing current yet, stepping forward lands on the first one on screen.
+    let from = match current {
+        Some(i) => i as i64,
+        None => first as i64 - if n > 0 { 1 } else { 0 },
+    };
+    Some(cmp::max(0, cmp::min(from + n, count as i64 - 1)) as usize)
+}
+
+/// The bytes on the edge of the `len` bytes from `start`, laid out `row_len` to a row,
+/// that are within the `page` bytes from `offset`, relative to `offset`.
+fn outline(start: u64, len: u64, row_len: u64, offset: u64, page: u64) -> Vec<(usize, usize)> {
+    let end = start + len;
+    let mut ranges: Vec<(usize, usize)> = vec![];
+    for at in cmp::max(start, offset)..cmp::min(end, offset + page) {
+        let x = at % row_len;
+        if x != 0 && x != row_len - 1 && at >= start + row_len && at + row_len < end {
+            continue;
+        }
+        let i = (at - offset) as usize;
+        if ranges.last().map_or(false, |&(_, last)| last == i) {
+            ranges.last_mut().unwrap().1 += 1;
+        } else {
+            ranges.push((i, i + 1));
+        }
+    }
+    ranges
+}
+
+/// What the text typed at the prompt is for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Prompt {
+    Search,
+    /// The name of a new bookmark of this many bytes from this offset.
+    Bookmark(u64, u64),
+    /// The file to write this many bytes from this offset to.
+    Write(u64, u64),
+}
+
+/// The first offset and the length of the bytes from `from` to `to`, both included.
+fn span((from, to): (u64, u64)) -> (u64, u64) {
+    (cmp::min(from, to), cmp::max(from, to) - cmp::min(from, to) + 1)
+}
+
+/// What is selected, and what can be done with it.
+fn describe(selection: (u64, u64), project: &Project) -> String {
+    let (start, len) = span(selection);
+    let names: Vec<&str> = project.bookmarks.iter()
+        .filter(|b| b.offset < start + len && start < b.offset + b.len)
+        .map(|b| b.name.as_str())
+        .collect();
+    let within = if names.is_empty() { String::new() } else { format!(" in {}", names.join(", ")) };
+    format!("{:#x}+{}{}: c offsets, x hexdump, w write, b bookmark", start, len, within)
+}
+
+/// Offset of string `i`, said with --verbose as it is selected.
+fn pick(runs: &[Run], i: usize) -> u64 {
+    let run = &runs[i];
+    debug!("string {:?} of {:?} at {:#x}: {:?}", i + 1, runs.len(), run.offset, run.text);
+    run.offset
+}
+
+const STATUS_BACKGROUND: Rgb = [24, 24, 24];
+const STATUS_INK: Rgb = [224, 224, 224];
+
+/// The stride in pixels, up to `widest`, of the first input from `offset` on.
+fn find_stride(view: &View, offset: u64, widest: u32) -> Result<Option<u32>, Error> {
+    let stride = match (view.pixels, view.bits) {
+        // Rows of a picture repeat byte for byte, whatever the pixels are.
+        (Some(p), _) => {
+            let size = p.size();
+            let sample = try!(view.sources[0].read(offset, stride::SAMPLE * size as u64));
+            stride::detect(&sample, widest as usize * size).map(|stride| (stride + size / 2) / size)
+        }
+        // Only strides of whole bytes are found.
+        (None, Some(_)) => {
+            let sample = try!(view.sources[0].read(offset, stride::SAMPLE));
+            stride::detect(&sample, widest as usize / 8).map(|stride| stride * 8)
+        }
+        (None, None) => {
+            let size = view.format.size() as u64;
+            let sample = try!(view.sources[0].read(offset, stride::SAMPLE * size));
+            stride::detect(&samples::decode(view.format, view.range, &sample), widest as usize)
+        }
+    };
+    Ok(stride.map(|stride| cmp::max(stride, 1) as u32))
+}
+
+/// The pixels of `pixel_bits` bits that `ranges` of bytes cover some of.
+fn sampled(ranges: Vec<(usize, usize)>, pixel_bits: u64) -> Vec<(usize, usize)> {
+    let bits = pixel_bits as usize;
+    if bits == 8 {
+        return ranges;
+    }
+    ranges.into_iter().map(|(start, end)| (start * 8 / bits, (end * 8 + bits - 1) / bits)).collect()
+}
+
+/// `stride`, or the panes `widest` pixels across, cut down to a whole number of
+/// `grain` pixels so that each row starts on a byte.
+fn in_grains(stride: Option<u32>, widest: u32, grain: u32) -> Option<u32> {
+    if grain == 1 {
+        return stride;
+    }
+    let row_len = stride.map_or(widest, |stride| cmp::min(stride, widest));
+    Some(cmp::max(row_len / grain, 1) * grain)
+}
+
+/// The stride shown, as the panes are `widest` bytes across.
+fn describe_stride(stride: Option<u32>, widest: u32) -> String {
+    match stride {
+        Some(stride) => format!("stride {} of {} bytes across", stride, widest),
+        None => format!("no stride: {} bytes across", widest),
+    }
+}
+
+/// A line of text along the bottom of the panes.
+fn status_line(text: &str, width: u32) -> Image {
+    let mut image = Image::new(width, font::HEIGHT);
+    image.fill(0, 0, width, font::HEIGHT, STATUS_BACKGROUND);
+    image.text(1, 1, text, STATUS_INK);
+    image
+}
+
+/// What to name the mappings of the process that `source` may be, and the rows to name
+/// them on: the first row of each mapping on screen, as long as it doesn't hide another.
+fn labels(source: &Source, offset: u64, row_bytes: u64, height: u32) -> Vec<(u32, String)> {
+    let mappings = match (&source.member, source.process) {
+        (&None, Some(process)) => &process.mappings[..],
+        _ => return vec![],
+    };
+    let mut labels: Vec<(u32, String)> = vec![];
+    for mapping in mappings.iter().filter(|mapping| mapping.offset + mapping.len() > offset) {
+        let row = mapping.offset.saturating_sub(offset) / row_bytes;
+        if row + font::HEIGHT as u64 > height as u64 {
+            break;
+        }
+        let row = row as u32;
+        if labels.last().map_or(true, |&(last, _)| row >= last + font::HEIGHT) {
+            labels.push((row, mapping.label()));
+        }
+    }
+    labels
+}
+
+/// `text` on a line of its own, no wider than `width`.
+fn label(text: &str, width: u32) -> Image {
+    let width = cmp::min(text.chars().count() as u32 * font::WIDTH + 2, width);
+    status_line(text, width)
+}
+
+/// What has been picked, typed and said in the window so far.
+struct State {
+    /// The string selected in the list.
+    selected: Option<usize>,
+    /// The hits of the last search in each input, and the one last gone to.
+    hits: Vec<Vec<Hit>>,
+    hit: Option<usize>,
+    /// The bookmarks of each input, in the sidecar of each, and the one last gone to.
+    projects: Vec<Project>,
+    bookmark: Option<usize>,
+    /// From the byte pressed to the byte under the mouse, while `dragging`, then released on.
+    selection: Option<(u64, u64)>,
+    /// The pane last clicked: what is selected, carved and bookmarked is of its input.
+    pane: usize,
+    dragging: bool,
+    /// What is being typed, and what for, while it is.
+    prompt: Option<(Prompt, String)>,
+    status: Option<String>,
+}
+
+impl State {
+    /// Types `input` at the prompt, or leaves it on `Escape`.
+    fn edit(&mut self, input: Input) {
+        match input {
+            Input::Char(c) => self.prompt.as_mut().unwrap().1.push(c),
+            Input::Backspace => { self.prompt.as_mut().unwrap().1.pop(); }
+            Input::Escape => self.prompt = None,
+            _ => (),
+        }
+    }
+}
+
+/// What each input does in the window. Those that move it say where to, if anywhere,
+/// with `offset` the first byte on screen.
+impl<'a> View<'a> {
+    /// Does what the text typed at the prompt was for.
+    fn answer(&self, state: &mut State, offset: u64) -> Result<Option<u64>, Error> {
+        let mut jump = None;
+        match state.prompt.take().unwrap() {
+            (Prompt::Bookmark(at, len), ref name) if !name.is_empty() => {
+                state.projects[state.pane].add(Bookmark{offset: at, len: len, name: name.clone()});
+                try!(state.projects[state.pane].save());
+                state.status = Some(format!("bookmarked {:#x}+{} as {}", at, len, name));
+            }
+            (Prompt::Bookmark(..), _) => state.status = Some("a bookmark needs a name".to_owned()),
+            (Prompt::Write(at, len), out) => state.status = Some(match carve::write(&self.sources[state.pane], at, len, &out) {
+                Ok(written) => format!("wrote {} bytes to {}", written, out),
+                Err(e) => e.to_string(),
+            }),
+            (Prompt::Search, text) => match Pattern::parse(&text) {
+                Ok(pattern) => {
+                    state.hits = try!(find_all(&self.sources, &pattern));
+                    let hits = &state.hits[0];
+                    let first = first_from(hits.iter().map(|hit| (hit.offset, hit.len)), offset);
+                    state.hit = step(None, first, hits.len(), 1);
+                    jump = state.hit.map(|i| hits[i].offset);
+                    state.status = Some(found(&text, hits));
+                }
+                Err(e) => state.status = Some(format!("{}: {}", text, e)),
+            },
+        }
+        Ok(jump)
+    }
+
+    /// Goes to string `i` of the list of the first input.
+    fn go_to_string(&self, state: &mut State, i: usize) -> Option<u64> {
+        let runs = &self.strings[0];
+        if i >= runs.len() {
+            return None;
+        }
+        state.selected = Some(i);
+        Some(pick(runs, i))
+    }
+
+    /// Goes `n` strings down the list from the one selected, or from the first on screen.
+    fn step_string(&self, state: &mut State, n: i64, offset: u64) -> Option<u64> {
+        let runs = &self.strings[0];
+        let first = first_from(runs.iter().map(|run| (run.offset, run.len)), offset);
+        state.selected = step(state.selected, first, runs.len(), n);
+        state.selected.map(|i| pick(runs, i))
+    }
+
+    /// Starts selecting from byte `at` of the input in `pane`.
+    fn press(&self, state: &mut State, pane: usize, at: u64) {
+        if pane != state.pane {
+            state.bookmark = None;
+        }
+        state.pane = pane;
+        state.selection = Some((at, at));
+        state.dragging = true;
+        state.status = Some(describe((at, at), &state.projects[pane]));
+    }
+
+    /// Selects up to byte `at`, or the last byte of the inputs past their end, in the
+    /// pane clicked whichever pane the mouse is over.
+    fn drag(&self, state: &mut State, at: u64) {
+        let from = state.selection.unwrap().0;
+        let to = cmp::min(at, self.len - 1);
+        state.selection = Some((from, to));
+        state.status = Some(describe((from, to), &state.projects[state.pane]));
+    }
+
+    /// Does `c` to the selection: says its offsets, dumps it, writes it out, bookmarks
+    /// it or deletes the bookmarks over it. Says whether anything happened.
+    fn act_on_selection(&self, state: &mut State, c: char) -> Result<bool, Error> {
+        let (start, len) = span(state.selection.unwrap());
+        let source = &self.sources[state.pane];
+        match c {
+            'c' => {
+                let offsets = carve::offsets(start, len);
+                println!("{}", offsets);
+                state.status = Some(offsets);
+            }
+            'x' => state.status = Some(match carve::hexdump(source, start, len, &mut io::stdout()) {
+                Ok(()) => format!("dumped {} bytes from {:#x} on standard output", len, start),
+                Err(e) => e.to_string(),
+            }),
+            'w' => {
+                let out = format!("{}.{:x}-{:x}.bin", source.file_name(), start, start + len);
+                state.prompt = Some((Prompt::Write(start, len), out));
+            }
+            'b' => state.prompt = Some((Prompt::Bookmark(start, len), String::new())),
+            _ => {
+                let project = &mut state.projects[state.pane];
+                let before = project.bookmarks.len();
+                project.bookmarks.retain(|b| !(b.offset < start + len && start < b.offset + b.len));
+                if project.bookmarks.len() == before {
+                    return Ok(false);
+                }
+                try!(project.save());
+                state.bookmark = None;
+                state.status = Some(format!("deleted {} bookmarks", before - project.bookmarks.len()));
+            }
+        }
+        Ok(true)
+    }
+
+    /// Goes to the next bookmark of the input in the pane last clicked, or the one before.
+    fn step_bookmark(&self, state: &mut State, n: i64, offset: u64) -> Option<u64> {
+        let bookmarks = &state.projects[state.pane].bookmarks;
+        let first = first_from(bookmarks.iter().map(|b| (b.offset, b.len)), offset);
+        state.bookmark = step(state.bookmark, first, bookmarks.len(), n);
+        let i = match state.bookmark {
+            Some(i) => i,
+            None => return None,
+        };
+        let b = &bookmarks[i];
+        state.status = Some(format!("bookmark {} of {} at {:#x}+{}: {}",
+                                    i + 1, bookmarks.len(), b.offset, b.len, b.name));
+        Some(b.offset)
+    }
+
+    /// Goes to the next hit in the first input, or the one before.
+    fn step_hit(&self, state: &mut State, n: i64, offset: u64) -> Option<u64> {
+        let hits = &state.hits[0];
+        let first = first_from(hits.iter().map(|hit| (hit.offset, hit.len)), offset);
+        state.hit = step(state.hit, first, hits.len(), n);
+        let i = match state.hit {
+            Some(i) => i,
+            None => return None,
+        };
+        let at = hits[i].offset;
+        state.status = Some(format!("hit {} of {} at {:#x}", i + 1, hits.len(), at));
+        Some(at)
+    }
+
+    /// The stride `c` asks for, rows of `stride` on screen now and up to `widest` pixels
+    /// going by `grain`: found from `offset` on, a grain wider or narrower, or none.
+    /// The one there is when none is found.
+    fn restride(&self, state: &mut State, c: char, offset: u64, stride: Option<u32>, widest: u32, grain: u32)
+                -> Result<Option<u32>, Error> {
+        let row_len = stride.map_or(widest, |stride| cmp::min(stride, widest));
+        let wanted = match c {
+            's' => match try!(find_stride(self, offset, widest)) {
+                Some(found) => Some(found),
+                None => {
+                    state.status = Some(format!("no stride found from {:#x}", offset));
+                    return Ok(stride);
+                }
+            },
+            '+' | '=' => Some(cmp::min(row_len + grain, widest)),
+            '-' => Some(cmp::max(row_len - grain, 1)),
+            _ => None,
+        };
+        let wanted = in_grains(wanted, widest, grain);
+        if wanted != stride {
+            state.status = Some(describe_stride(wanted, widest));
+        }
+        Ok(wanted)
+    }
+}
+
+pub fn run<R: Renderer + ?Sized>(renderer: &mut R, view: &View) -> Result<(), Error> {
+    let (width, height) = renderer.size();
+    debug!("{:?}x{:?} = {:?}", width, height, width * height);
+    // From left to right: the panes, the list of strings, the entropy graph, the minimap.
+    let minimap_width = if !view.minimap || width < 4 * minimap::WIDTH {
+        0
+    } else if minimap::can_sample(&view.sources[0]) {
+        minimap::WIDTH
+    } else {
+        debug!("no minimap of {:?}: it would have to be read through", view.sources[0].name());
+        0
+    };
+    let graph_width = match view.entropy {
+        Some(_) if width - minimap_width >= 4 * entropy::WIDTH => entropy::WIDTH,
+        _ => 0,
+    };
+    let list_width = if view.strings.is_empty() {
+        0
+    } else {
+        cmp::min(LIST_COLUMNS * font::WIDTH, (width - minimap_width - graph_width) / 3)
+    };
+    let panes_width = width - list_width - graph_width - minimap_width;
+    let minimap_left = width - minimap_width;
+    let graph_left = minimap_left - graph_width;
+    let widest = view.layout.row_len(panes_width);
+    let pane_height = view.layout.pane_height(height);
+    if widest == 0 || pane_height == 0 {
+        return Err(Error::Layout(width, height));
+    }
+    let minimap = match minimap_width {
+        0 => None,
+        _ => Some(try!(Minimap::new(&view.sources[0], view.mode, height, view.len))),
+    };
+    let levels = match (graph_width, &view.entropy) {
+        (0, _) | (_, &None) => None,
+        (_, &Some((block, ref series))) => Some(entropy::levels(series, block, view.len, height)),
+    };
+
+    // All panes always show the same offsets: scrolling moves this one row counter.
+    let mut row: u64 = 0;
+    let mut loaded_row = None;
+    // With `view.pixels`, what the bytes on screen decode to, a picture per pane.
+    let mut pictures: Vec<Image> = vec![];
+    let mut projects = vec![];
+    for source in &view.sources {
+        projects.push(try!(Project::load(&source.project())));
+    }
+    let mut state = State {
+        selected: None,
+        hits: vec![],
+        hit: None,
+        projects: projects,
+        bookmark: None,
+        selection: None,
+        pane: 0,
+        dragging: false,
+        prompt: None,
+        status: None,
+    };
+    let mut dirty = true;
+    let mut first_frame = true;
+    let mut scene = Scene {
+        layout: view.layout,
+        mode: view.mode,
+        width: panes_width,
+        height: height,
+        buffers: vec![],
+        classes: vec![],
+        marks: vec![],
+        panels: vec![],
+        graph: None,
+        stride: view.stride,
+    };
+    // Bits per pixel, so per row, `row_len` being in pixels.
+    let pixel_bits = match (view.pixels, view.bits) {
+        (Some(p), _) => p.size() * 8,
+        (None, Some(_)) => 1,
+        (None, None) => view.format.size() * 8,
+    } as u64;
+    // Pixels per row go by this many, for rows to start on a byte.
+    let grain = cmp::max(8 / pixel_bits, 1) as u32;
+    if widest < grain {
+        return Err(Error::Layout(width, height));
+    }
+    // A planar picture is decoded from its first byte only, so the screen shows one
+    // frame and moves a frame at a time.
+    let planar = view.pixels.map_or(false, |p| p.planar());
+    let frame_height = if planar { view.frame_height.unwrap_or(pane_height) } else { pane_height };
+    // Bytes on screen from the top left, chroma planes included.
+    let page_of = |row_len: u32| match view.pixels {
+        Some(p) => p.frame_len(row_len, frame_height),
+        None => row_len as u64 * pixel_bits / 8 * pane_height as u64,
+    };
+    // Bytes the screen moves by for each row scrolled.
+    let row_step_of = |row_len: u32| if planar { page_of(row_len) } else { row_len as u64 * pixel_bits / 8 };
+    scene.stride = in_grains(scene.stride, widest, grain);
+    if view.auto_stride {
+        scene.stride = in_grains(try!(find_stride(view, 0, widest)), widest, grain);
+        state.status = Some(describe_stride(scene.stride, widest));
+        debug!("{}", state.status.as_ref().unwrap());
+    }
+    let mut row_len = scene.row_len();
+    let mut row_bytes = row_len as u64 * pixel_bits / 8;
+    let mut page = page_of(row_len);
+    let mut row_step = row_step_of(row_len);
+    let mut last_row = (view.len - 1) / row_step;
+
+    if let Some((ref text, ref pattern)) = view.search {
+        state.hits = try!(find_all(&view.sources, pattern));
+        state.status = Some(found(text, &state.hits[0]));
+        debug!("{}", state.status.as_ref().unwrap());
+        if let Some(first) = state.hits[0].first() {
+            state.hit = Some(0);
+            row = cmp::min(first.offset / row_step, last_row);
+        }
+    }
+
+    loop {
+        let offset = row * row_step;
+        if dirty {
+            if loaded_row != Some(row) {
+                scene.buffers.clear();
+                pictures.clear();
+                for source in &view.sources {
+                    let bytes = try!(source.read(offset, page));
+                    match (view.pixels, view.bits) {
+                        // Marks go over the picture in the grey of each pixel.
+                        (Some(p), _) => {
+                            let picture = pixels::decode(p, row_len, frame_height, &bytes);
+                            scene.buffers.push(pixels::luma(&picture));
+                            pictures.push(picture);
+                        }
+                        (None, Some(order)) => scene.buffers.push(bits::expand(order, &bytes)),
+                        (None, None) => scene.buffers.push(samples::decode(view.format, view.range, &bytes)),
+                    }
+                }
+                scene.classes.clear();
+                if view.mode == Mode::Class && view.layout != Layout::Xor && view.pixels.is_none() {
+                    for (source, buffer) in view.sources.iter().zip(&scene.buffers) {
+                        let len = buffer.len() * pixel_bits as usize / 8;
+                        let classes = try!(classify::page(source, offset, len, view.block));
+                        // Each pixel is in the class of its first byte.
+                        scene.classes.push(match pixel_bits {
+                            1 => classes.iter().flat_map(|&class| vec![class; 8]).collect(),
+                            _ => classes.chunks(pixel_bits as usize / 8).map(|c| c[0]).collect(),
+                        });
+                    }
+                }
+                if scene.buffers.len() == 2 {
+                    debug!("{:?} differing bytes in [{:?}, {:?})",
+                             diff::differing(&scene.buffers[0], &scene.buffers[1]), offset, offset + page);
+                }
+                loaded_row = Some(row);
+            }
+
+            scene.marks.clear();
+            if view.layout != Layout::Xor {
+                for (i, source) in view.sources.iter().enumerate() {
+                    let ranges = within(source.gaps().into_iter(), offset, page);
+                    scene.marks.push(Marks{buffer: i, mark: Mark::Gap, ranges: sampled(ranges, pixel_bits)});
+                }
+                for (i, runs) in view.strings.iter().enumerate() {
+                    let ranges = within(runs.iter().map(|run| (run.offset, run.len)), offset, page);
+                    let ranges = sampled(ranges, pixel_bits);
+                    scene.marks.push(Marks{buffer: i, mark: Mark::String, ranges: ranges});
+                }
+                for (i, hits) in state.hits.iter().enumerate() {
+                    let ranges = within(hits.iter().map(|hit| (hit.offset, hit.len)), offset, page);
+                    let ranges = sampled(ranges, pixel_bits);
+                    scene.marks.push(Marks{buffer: i, mark: Mark::Hit, ranges: ranges});
+                }
+            }
+            // The selection is drawn over its pane, and the bookmarks of each input over
+            // its own, whatever the layout.
+            let picked = state.selection.map_or(vec![], |s| within(Some(span(s)).into_iter(), offset, page));
+            scene.marks.push(Marks{buffer: state.pane, mark: Mark::Selection, ranges: sampled(picked, pixel_bits)});
+            for (i, project) in state.projects.iter().enumerate() {
+                if i == 0 || view.layout != Layout::Xor {
+                    let mut ranges = vec![];
+                    for b in &project.bookmarks {
+                        ranges.extend(outline(b.offset, b.len, row_bytes, offset, page));
+                    }
+                    scene.marks.push(Marks{buffer: i, mark: Mark::Bookmark, ranges: sampled(ranges, pixel_bits)});
+                }
+            }
+
+            scene.panels.clear();
+            // Pictures are drawn over the panes, so their marks have to go over them.
+            for (i, picture) in pictures.iter().enumerate() {
+                let mut picture = picture.clone();
+                for marks in scene.marks.iter().filter(|marks| marks.buffer == i) {
+                    raster::highlight(&mut picture, 0, 0, row_len, &scene.buffers[i], &marks.ranges, marks.mark);
+                }
+                // A frame of another height than the panes is cut off or left black under.
+                if picture.height != pane_height {
+                    let mut framed = Image::new(row_len, pane_height);
+                    framed.blit(&picture, 0, 0);
+                    picture = framed;
+                }
+                scene.panels.push(Panel{left: scene.pane_left(i), top: scene.pane_top(i), image: picture});
+            }
+            if !pictures.is_empty() {
+                scene.marks.clear();
+            }
+            for (i, source) in view.sources.iter().enumerate() {
+                let (left, top) = (scene.pane_left(i), scene.pane_top(i));
+                for (row, text) in labels(source, offset, row_bytes, pane_height) {
+                    scene.panels.push(Panel{left: left, top: top + row, image: label(&text, row_len)});
+                }
+                if let Layout::Grid{..} = view.layout {
+                    let image = label(&font::tail(&source.name(), widest.saturating_sub(2)), widest);
+                    scene.panels.push(Panel{left: left, top: top + pane_height, image: image});
+                }
+            }
+            if list_width > 0 {
+                let runs = &view.strings[0];
+                let first = first_from(runs.iter().map(|run| (run.offset, run.len)), offset);
+                let list = strings::list(runs, first, state.selected, list_width, height);
+                scene.panels.push(Panel{left: panes_width, top: 0, image: list});
+            }
+            if let Some(ref minimap) = minimap {
+                scene.panels.push(Panel{left: minimap_left, top: 0, image: minimap.draw(offset, page)});
+            }
+            scene.graph = levels.as_ref().map(|levels| {
+                let end = cmp::min(offset + page, view.len);
+                let row_of = |at: u64| (at * height as u64 / view.len) as u32;
+                Graph{left: graph_left, levels: levels.clone(), visible: (row_of(offset), row_of(end - 1))}
+            });
+            let line = match (&state.prompt, &state.status) {
+                (&Some((Prompt::Search, ref text)), _) => Some(format!("/{}_", text)),
+                (&Some((Prompt::Bookmark(..), ref text)), _) => Some(format!("name: {}_", text)),
+                (&Some((Prompt::Write(..), ref text)), _) => Some(format!("write to: {}_", text)),
+                (&None, &Some(ref text)) => Some(text.clone()),
+                (&None, &None) => None,
+            };
+            if let Some(line) = line {
+                let image = status_line(&line, panes_width);
+                scene.panels.push(Panel{left: 0, top: height.saturating_sub(font::HEIGHT), image: image});
+            }
+
+            try!(renderer.load(&scene));
+            dirty = false;
+        }
+        try!(renderer.draw());
+
+        if first_frame {
+            first_frame = false;
+            if try!(first(renderer, &scene, view)) {
+                return Ok(());
+            }
+        }
+
+        let mut scroll: i64 = 0;
+        // Offset to bring to the top of the screen.
+        let mut jump = None;
+        let said = state.status.clone();
+        for input in renderer.input() {
+            if state.prompt.is_some() {
+                match input {
+                    Input::Quit => return Ok(()),
+                    Input::Enter => jump = try!(view.answer(&mut state, offset)).or(jump),
+                    input => state.edit(input),
+                }
+                dirty = true;
+                continue;
+            }
+
+            match input {
+                Input::Quit => return Ok(()),
+                Input::Scroll(rows) if planar => scroll += rows.signum(),
+                Input::Scroll(rows) => scroll += rows,
+                Input::Page(pages) if planar => scroll += pages,
+                Input::Page(pages) => scroll += pages * pane_height as i64,
+                Input::Home => scroll = -(row as i64),
+                Input::Click(x, y) | Input::Drag(x, y) if minimap.is_some() && !state.dragging && x >= minimap_left => {
+                    // The byte clicked comes to the middle of the screen.
+                    match minimap.as_ref().unwrap().offset(x - minimap_left, y) {
+                        Some(at) => jump = Some(at.saturating_sub(page / 2)),
+                        None => continue,
+                    }
+                }
+                Input::Click(x, y) | Input::Drag(x, y) if graph_width > 0 && !state.dragging && x >= graph_left => {
+                    let at = y as u64 * view.len / height as u64;
+                    jump = Some(at.saturating_sub(page / 2));
+                }
+                Input::Click(x, y) if list_width > 0 && x >= panes_width => {
+                    let runs = &view.strings[0];
+                    let i = first_from(runs.iter().map(|run| (run.offset, run.len)), offset) +
+                        (y / font::HEIGHT) as usize;
+                    match view.go_to_string(&mut state, i) {
+                        Some(at) => jump = Some(at),
+                        None => continue,
+                    }
+                }
+                Input::Select(n) if list_width > 0 && !view.strings[0].is_empty() => {
+                    jump = view.step_string(&mut state, n, offset);
+                }
+                Input::Click(x, y) => match scene.byte_at(x, y) {
+                    Some((i, at)) if offset + at as u64 * pixel_bits / 8 < view.len =>
+                        view.press(&mut state, i, offset + at as u64 * pixel_bits / 8),
+                    _ => continue,
+                },
+                // To the last byte of the pixel under the mouse.
+                Input::Drag(x, y) if state.dragging => match scene.byte_at(x, y) {
+                    Some((_, at)) => view.drag(&mut state, offset + ((at as u64 + 1) * pixel_bits + 7) / 8 - 1),
+                    None => continue,
+                },
+                Input::Release => {
+                    state.dragging = false;
+                    continue;
+                }
+                Input::Escape => match state.selection.take() {
+                    Some(_) => state.status = None,
+                    None => return Ok(()),
+                },
+                Input::Char('/') => state.prompt = Some((Prompt::Search, String::new())),
+                Input::Char(c) if state.selection.is_some() && "cxwbd".contains(c) => {
+                    if !try!(view.act_on_selection(&mut state, c)) {
+                        continue;
+                    }
+                }
+                Input::Char(c) if (c == '.' || c == ',') && !state.projects[state.pane].bookmarks.is_empty() => {
+                    jump = view.step_bookmark(&mut state, if c == '.' { 1 } else { -1 }, offset).or(jump);
+                }
+                Input::Char(c) if "s+=-0".contains(c) => {
+                    let stride = try!(view.restride(&mut state, c, offset, scene.stride, widest, grain));
+                    if stride == scene.stride {
+                        continue;
+                    }
+                    // The byte at the top left stays on the top row.
+                    scene.stride = stride;
+                    row_len = scene.row_len();
+                    row_bytes = row_len as u64 * pixel_bits / 8;
+                    page = page_of(row_len);
+                    row_step = row_step_of(row_len);
+                    last_row = (view.len - 1) / row_step;
+                    loaded_row = None;
+                    jump = Some(offset);
+                }
+                Input::Char(c) if (c == 'n' || c == 'N') && !state.hits.is_empty() => {
+                    jump = view.step_hit(&mut state, if c == 'n' { 1 } else { -1 }, offset).or(jump);
+                }
+                Input::Drag(..) | Input::Select(..) | Input::Char(..) | Input::Backspace | Input::Enter => continue,
+            }
+            dirty = true;
+        }
+        // Saying something new is worth a frame, whatever else did or didn't change.
+        if state.status != said {
+            if let Some(ref status) = state.status {
+                debug!("{}", status);
+            }
+            dirty = true;
+        }
+
+        let previous = row;
+        if let Some(at) = jump {
+            row = cmp::min(at / row_step, last_row);
+        } else {
+            row = if scroll < 0 {
+                row.saturating_sub(-scroll as u64)
+            } else {
+                cmp::min(row + scroll as u64, last_row)
+            };
+        }
+        dirty |= row != previous;
+    }
+}
+
+/// Checks the first frame and saves it as asked, and says whether that was all.
+fn first<R: Renderer + ?Sized>(renderer: &R, scene: &Scene, view: &View) -> Result<bool, Error> {
+    if view.check {
+        try!(check(renderer, scene));
+    }
+    match view.png {
+        Some(png) => {
+            let image = try!(renderer.frame());
+            try!(image.save_png(Path::new(png)).map_err(|e| Error::Output(png.to_owned(), e)));
+            debug!("wrote {:?}", png);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Shows a thumbnail of each of `members` of the first input, an archive, until one is
+/// clicked, and says which, if any.
+pub fn mosaic<R: Renderer + ?Sized>(renderer: &mut R, view: &View, members: &[Member])
+                                    -> Result<Option<usize>, Error> {
+    let (width, height) = renderer.size();
+    let sources: Vec<Source> = members.iter().map(|member| view.sources[0].member(member.clone())).collect();
+    let names: Vec<String> = members.iter().map(|member| member.name.clone()).collect();
+    let mosaic = try!(Mosaic::new(&sources, &names, view.mode, width, height));
+    let scene = Scene {
+        layout: Layout::Single,
+        mode: view.mode,
+        width: width,
+        height: height,
+        buffers: vec![vec![]],
+        classes: vec![],
+        marks: vec![],
+        panels: vec![Panel{left: 0, top: 0, image: mosaic.image.clone()}],
+        graph: None,
+        stride: None,
+    };
+    try!(renderer.load(&scene));
+    try!(renderer.draw());
+    if try!(first(renderer, &scene, view)) {
+        return Ok(None);
+    }
+    loop {
+        for input in renderer.input() {
+            match input {
+                Input::Quit | Input::Escape => return Ok(None),
+                Input::Click(x, y) => if let Some(i) = mosaic.pick(x, y) {
+                    return Ok(Some(i));
+                },
+                _ => (),
+            }
+        }
+        try!(renderer.draw());
+    }
+}
+
+/// Compares the frame just drawn with the CPU rasterization of `scene`.
+fn check<R: Renderer + ?Sized>(renderer: &R, scene: &Scene) -> Result<(), Error> {
+    let (width, height) = renderer.size();
+    let expected = scene.rasterize(width, height);
+    let drawn = try!(renderer.frame());
+    let total = expected.pixels.len();
+    if (drawn.width, drawn.height) != (width, height) {
+        return Err(Error::Check(total, total, (0, 0)));
+    }
+    let found = raster::mismatches(&expected, &drawn);
+    if !found.is_empty() {
+        return Err(Error::Check(found.len(), total, found[0]));
+    }
+    debug!("all {:?} pixels match the CPU rasterization", total);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::io::Read;
+
+    use fixtures::{self, Script};
+    use palette::Mode;
+    use pixels::{self, Pixels};
+    use super::{run, Input, Layout, Renderer, View};
+
+    #[test]
+    fn grid() {
+        assert_eq!(Layout::grid(2), Layout::Grid{columns: 2, rows: 1});
+        assert_eq!(Layout::grid(3), Layout::Grid{columns: 2, rows: 2});
+        assert_eq!(Layout::grid(5), Layout::Grid{columns: 3, rows: 2});
+    }
+
+    #[test]
+    fn carve_from_second_pane() {
+        let a = fixtures::write("pane-a.bin", &[b'a'; 256]);
+        let b = fixtures::write("pane-b.bin", &(0..256).map(|i| i as u8).collect::<Vec<u8>>());
+        // Panes of 64 bytes across, the second from x = 65: bytes 67 to 70 of b.
+        let out = fixtures::out("pane-b.bin.43-47.bin");
+
+        let view = View::new(vec![a.to_str().unwrap(), b.to_str().unwrap()], Layout::SideBySide, Mode::Four).unwrap();
+        let inputs = vec![Input::Click(68, 1), Input::Drag(71, 1), Input::Release, Input::Char('w'), Input::Enter];
+        run(&mut Script::new(129, 4, vec![inputs]), &view).unwrap();
+
+        let mut carved = vec![];
+        File::open(&out).unwrap().read_to_end(&mut carved).unwrap();
+        assert_eq!(carved, vec![67, 68, 69, 70]);
+    }
+
+    #[test]
+    fn scroll_planar_frames() {
+        // Frames of 4x2 YUV 4:2:0, each 8 bytes of luma then 2 of each chroma: grey,
+        // then red, then blue.
+        let frames: Vec<Vec<u8>> = vec![vec![128; 12], vec![76, 76, 76, 76, 76, 76, 76, 76, 85, 85, 255, 255],
+                                         vec![29, 29, 29, 29, 29, 29, 29, 29, 255, 255, 107, 107]];
+        let path = fixtures::write("frames.yuv", &frames.concat());
+
+        let mut view = View::new(vec![path.to_str().unwrap()], Layout::Single, Mode::Four).unwrap();
+        view.pixels = Some(Pixels::Yuv420);
+        view.stride = Some(4);
+        view.frame_height = Some(2);
+        // Scrolling by rows goes a frame at a time, whatever the rows.
+        let mut script = Script::new(4, 3, vec![vec![Input::Scroll(16)]]);
+        run(&mut script, &view).unwrap();
+        let frame = script.cpu.frame().unwrap();
+        let red = pixels::decode(Pixels::Yuv420, 4, 2, &frames[1]);
+        assert_eq!(&frame.pixels[..8], &red.pixels[..]);
+        // Rows under the frame are left black.
+        assert_eq!(&frame.pixels[8..], &[[0, 0, 0]; 4][..]);
+
+        // And so does paging.
+        script.inputs = vec![vec![Input::Page(2)]];
+        run(&mut script, &view).unwrap();
+        let blue = pixels::decode(Pixels::Yuv420, 4, 2, &frames[2]);
+        assert_eq!(&script.cpu.frame().unwrap().pixels[..8], &blue.pixels[..]);
+    }
+}
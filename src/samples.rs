@@ -0,0 +1,130 @@
+//! Inputs made of multi-byte samples, as sensor dumps, audio and framebuffers are:
+//! each sample is drawn as one pixel, its value scaled from the range of the input
+//! to that of a byte before the colour mode sees it.
+
+use std::io::{self, Read};
+
+/// How the bytes of an input make up samples.
+#[derive(Debug, Clone, Copy, PartialEq, RustcDecodable)]
+pub enum Format {
+    U8,
+    I8,
+    U16le,
+    U16be,
+    I16le,
+    I16be,
+    U32le,
+    U32be,
+    I32le,
+    I32be,
+    F32le,
+    F32be,
+    F64le,
+    F64be,
+}
+
+impl Format {
+    /// Bytes per sample.
+    pub fn size(&self) -> usize {
+        match *self {
+            Format::U8 | Format::I8 => 1,
+            Format::U16le | Format::U16be | Format::I16le | Format::I16be => 2,
+            Format::U32le | Format::U32be | Format::I32le | Format::I32be | Format::F32le | Format::F32be => 4,
+            Format::F64le | Format::F64be => 8,
+        }
+    }
+
+    /// The value of the sample in the first `size` bytes of `bytes`.
+    pub fn value(&self, bytes: &[u8]) -> f64 {
+        let n = self.size();
+        let mut bits = 0u64;
+        for i in 0..n {
+            let byte = match *self {
+                Format::U16be | Format::I16be | Format::U32be | Format::I32be | Format::F32be | Format::F64be =>
+                    bytes[i],
+                _ => bytes[n - 1 - i],
+            };
+            bits = bits << 8 | byte as u64;
+        }
+        match *self {
+            Format::U8 | Format::U16le | Format::U16be | Format::U32le | Format::U32be => bits as f64,
+            Format::I8 => bits as u8 as i8 as f64,
+            Format::I16le | Format::I16be => bits as u16 as i16 as f64,
+            Format::I32le | Format::I32be => bits as u32 as i32 as f64,
+            Format::F32le | Format::F32be => f32::from_bits(bits as u32) as f64,
+            Format::F64le | Format::F64be => f64::from_bits(bits),
+        }
+    }
+}
+
+/// The lowest and highest finite values of the whole samples of `reader`, if any.
+pub fn range<R: Read>(format: Format, mut reader: R) -> io::Result<Option<(f64, f64)>> {
+    let size = format.size();
+    let mut found: Option<(f64, f64)> = None;
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut filled = 0;
+    loop {
+        let read = try!(reader.read(&mut buffer[filled..]));
+        filled += read;
+        let whole = filled / size * size;
+        for sample in buffer[..whole].chunks(size) {
+            let value = format.value(sample);
+            if value.is_finite() {
+                found = Some(found.map_or((value, value), |(low, high)| (low.min(value), high.max(value))));
+            }
+        }
+        // A sample cut in two by the read goes at the start of the next one.
+        for i in whole..filled {
+            buffer[i - whole] = buffer[i];
+        }
+        filled -= whole;
+        if read == 0 {
+            return Ok(found);
+        }
+    }
+}
+
+/// One byte per whole sample of `bytes`, from 0 for `range.0` up to 255 for `range.1`.
+/// Values outside the range, infinities included, are clamped to it and NaNs are 0.
+pub fn decode(format: Format, range: (f64, f64), bytes: &[u8]) -> Vec<u8> {
+    if format == Format::U8 && range == (0f64, 255f64) {
+        return bytes.to_vec();
+    }
+    let (low, high) = range;
+    let spread = if high > low { high - low } else { 1f64 };
+    bytes.chunks(format.size())
+        .filter(|sample| sample.len() == format.size())
+        .map(|sample| ((format.value(sample) - low) / spread * 255f64).round().max(0f64).min(255f64) as u8)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, range, Format};
+
+    #[test]
+    fn values() {
+        assert_eq!(Format::U16le.value(&[0x34, 0x12]), 0x1234 as f64);
+        assert_eq!(Format::U16be.value(&[0x12, 0x34]), 0x1234 as f64);
+        assert_eq!(Format::I8.value(&[0xff]), -1f64);
+        assert_eq!(Format::I16be.value(&[0x80, 0x00]), -32768f64);
+        assert_eq!(Format::I32le.value(&[0xfe, 0xff, 0xff, 0xff]), -2f64);
+        assert_eq!(Format::F32le.value(&[0, 0, 0xc0, 0x3f]), 1.5f64);
+        assert_eq!(Format::F64be.value(&[0xc0, 0, 0, 0, 0, 0, 0, 0]), -2f64);
+    }
+
+    #[test]
+    fn ranges() {
+        // NaNs are left out of the range, then drawn as its bottom.
+        let floats: Vec<u8> = vec![-1f32, 0.5, 3f32, 0f32 / 0f32].into_iter()
+            .flat_map(|f| {
+                let bits = f.to_bits();
+                (0..4).map(move |i| (bits >> (8 * i)) as u8)
+            })
+            .collect();
+        let found = range(Format::F32le, &floats[..]).unwrap().unwrap();
+        assert_eq!(found, (-1f64, 3f64));
+        assert_eq!(decode(Format::F32le, found, &floats), vec![0, 96, 255, 0]);
+        assert_eq!(range(Format::U32le, &[1, 2, 3][..]).unwrap(), None);
+    }
+}
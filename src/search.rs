@@ -0,0 +1,121 @@
+//! Finding bytes: text, hex with `??` wildcards or regular expressions,
+//! all run as `regex::bytes` over the input a chunk at a time.
+
+use std::io::{self, Read};
+
+use regex::bytes::Regex;
+
+/// Bytes searched at a time.
+const CHUNK: u64 = 1 << 20;
+
+/// Bytes searched again at the start of the next chunk, so that hits across the
+/// boundary are found. Regular expressions matching more than this may be cut short.
+const OVERLAP: usize = 4096;
+
+/// Hits past this many are not looked for.
+pub const MAX_HITS: usize = 100000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hit {
+    pub offset: u64,
+    pub len: u64,
+}
+
+pub struct Pattern {
+    regex: Regex,
+}
+
+impl Pattern {
+    /// `hex:4d 5a ?? 00` for bytes where `??` is any byte, `re:<regex>` for a regular
+    /// expression over bytes (`(?-u)` to match bytes rather than UTF-8), anything else
+    /// for the text itself.
+    pub fn parse(s: &str) -> Result<Pattern, String> {
+        let (flags, expression) = if s.starts_with("re:") {
+            ("", s[3..].to_owned())
+        } else if s.starts_with("hex:") {
+            ("(?s-u)", try!(hex(&s[4..])))
+        } else {
+            ("(?-u)", s.bytes().map(|b| format!("\\x{:02x}", b)).collect())
+        };
+        if expression.is_empty() {
+            return Err("empty pattern".to_owned());
+        }
+        let regex = try!(Regex::new(&format!("{}{}", flags, expression)).map_err(|e| e.to_string()));
+        Ok(Pattern{regex: regex})
+    }
+
+    /// Every non-empty match in `reader`, by offset, no more than `MAX_HITS`.
+    pub fn find<R: Read>(&self, mut reader: R) -> io::Result<Vec<Hit>> {
+        let mut hits = vec![];
+        let mut buffer = vec![];
+        // Offset of `buffer[0]` in the input.
+        let mut base = 0u64;
+        loop {
+            let read = try!(reader.by_ref().take(CHUNK).read_to_end(&mut buffer)) as u64;
+            let eof = read < CHUNK;
+            // Matches starting in the overlap are left for the next chunk, which sees all of them.
+            let limit = if eof { buffer.len() } else { buffer.len().saturating_sub(OVERLAP) };
+            for m in self.regex.find_iter(&buffer) {
+                if m.start() >= limit {
+                    break;
+                }
+                if m.end() > m.start() {
+                    hits.push(Hit{offset: base + m.start() as u64, len: (m.end() - m.start()) as u64});
+                    if hits.len() == MAX_HITS {
+                        return Ok(hits);
+                    }
+                }
+            }
+            if eof {
+                return Ok(hits);
+            }
+            buffer.drain(..limit);
+            base += limit as u64;
+        }
+    }
+}
+
+/// Hex digit pairs as a regular expression, whitespace ignored.
+fn hex(s: &str) -> Result<String, String> {
+    let digits: Vec<char> = s.chars().filter(|c| !c.is_whitespace()).collect();
+    if digits.len() % 2 != 0 {
+        return Err(format!("odd number of hex digits in {:?}", s));
+    }
+    let mut expression = String::new();
+    for pair in digits.chunks(2) {
+        match (pair[0], pair[1]) {
+            ('?', '?') => expression.push('.'),
+            (hi, lo) if hi.is_digit(16) && lo.is_digit(16) => {
+                expression.push_str("\\x");
+                expression.push(hi);
+                expression.push(lo);
+            }
+            (hi, lo) => return Err(format!("{}{} is neither a hex byte nor ??", hi, lo)),
+        }
+    }
+    Ok(expression)
+}
+
+#[cfg(test)]
+mod tests {
+    use fixtures;
+    use super::{Hit, Pattern};
+
+    fn hits(pattern: &str) -> Vec<Hit> {
+        Pattern::parse(pattern).unwrap().find(&fixtures::read("strings.bin")[..]).unwrap()
+    }
+
+    #[test]
+    fn patterns() {
+        assert_eq!(hits("lib"), vec![Hit{offset: 215, len: 3}, Hit{offset: 219, len: 3}]);
+        assert_eq!(hits("hex:5c 00 ?? 00").iter().map(|hit| hit.offset).collect::<Vec<_>>(), vec![106, 122]);
+        assert_eq!(hits("re:[0-9]{4}").iter().map(|hit| hit.offset).collect::<Vec<_>>(), vec![337]);
+    }
+
+    #[test]
+    fn bad_patterns() {
+        for bad in &["", "hex:", "hex:4d5", "hex:4g", "re:("] {
+            assert!(Pattern::parse(bad).is_err(), "{:?}", bad);
+        }
+    }
+}
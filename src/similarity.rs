@@ -0,0 +1,208 @@
+//! `minrs similarity <a> <b>`: how alike two inputs are, in numbers rather than
+//! pictures, to cluster variants of a sample or builds of a firmware without looking at
+//! every pair. Distances go from 0 for the same distribution to 1 for ones with nothing
+//! in common.
+
+use std::cmp;
+use std::collections::BTreeMap;
+use std::io::{self, Read};
+
+use rustc_serialize::json::{Json, ToJson};
+
+use entropy;
+use error::Error;
+use source::Source;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Similarity {
+    /// What each input is called.
+    pub names: (String, String),
+    /// Bytes in each input.
+    pub lens: (u64, u64),
+    /// Jensen-Shannon distance between how often each byte value occurs in either.
+    pub histogram: f64,
+    /// The same between how often each pair of consecutive bytes does.
+    pub digraphs: f64,
+    /// Pearson correlation of the entropy of the blocks at the same offsets in both,
+    /// none when there are fewer than two of them or either doesn't vary.
+    pub entropy: Option<f64>,
+    /// Bytes per block.
+    pub block: usize,
+}
+
+impl ToJson for Similarity {
+    fn to_json(&self) -> Json {
+        let mut object = BTreeMap::new();
+        object.insert("a".to_owned(), self.names.0.to_json());
+        object.insert("b".to_owned(), self.names.1.to_json());
+        object.insert("a_len".to_owned(), self.lens.0.to_json());
+        object.insert("b_len".to_owned(), self.lens.1.to_json());
+        object.insert("histogram_distance".to_owned(), self.histogram.to_json());
+        object.insert("digraph_distance".to_owned(), self.digraphs.to_json());
+        object.insert("entropy_correlation".to_owned(), self.entropy.to_json());
+        object.insert("block".to_owned(), self.block.to_json());
+        Json::Object(object)
+    }
+}
+
+/// What is compared of an input, all counted in one pass over it.
+struct Counts {
+    /// How many times each byte value occurs.
+    histogram: Vec<u64>,
+    /// How many times each pair of consecutive bytes does.
+    digraphs: Vec<u64>,
+    /// Entropy of each block, as `entropy::blocks` has it.
+    entropy: Vec<f64>,
+    len: u64,
+}
+
+/// The counts of `reader`, its entropy taken `block` bytes at a time.
+fn counts<R: Read>(mut reader: R, block: usize) -> io::Result<Counts> {
+    let mut counts = Counts{histogram: vec![0u64; 256], digraphs: vec![0u64; 256 * 256], entropy: vec![], len: 0};
+    // Each byte value in the block so far, and the bytes in it.
+    let mut in_block = [0u64; 256];
+    let mut filled = 0;
+    let mut previous: Option<u8> = None;
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = try!(reader.read(&mut buffer));
+        if read == 0 {
+            if filled > 0 {
+                counts.entropy.push(entropy::entropy(&in_block, filled));
+            }
+            return Ok(counts);
+        }
+        for &b in &buffer[..read] {
+            counts.histogram[b as usize] += 1;
+            if let Some(a) = previous {
+                counts.digraphs[(a as usize) << 8 | b as usize] += 1;
+            }
+            previous = Some(b);
+            in_block[b as usize] += 1;
+            filled += 1;
+            if filled == block as u64 {
+                counts.entropy.push(entropy::entropy(&in_block, filled));
+                in_block = [0u64; 256];
+                filled = 0;
+            }
+        }
+        counts.len += read as u64;
+    }
+}
+
+/// Jensen-Shannon distance, the square root of the divergence in bits, between the
+/// distributions that `p` and `q` count.
+pub fn distance(p: &[u64], q: &[u64]) -> f64 {
+    let (p_total, q_total) = (p.iter().sum::<u64>() as f64, q.iter().sum::<u64>() as f64);
+    if p_total == 0f64 || q_total == 0f64 {
+        return if p_total == q_total { 0f64 } else { 1f64 };
+    }
+    let mut divergence = 0f64;
+    for (&p, &q) in p.iter().zip(q) {
+        let (p, q) = (p as f64 / p_total, q as f64 / q_total);
+        let m = (p + q) / 2f64;
+        if p > 0f64 {
+            divergence += p * (p / m).log2();
+        }
+        if q > 0f64 {
+            divergence += q * (q / m).log2();
+        }
+    }
+    (divergence / 2f64).max(0f64).sqrt().min(1f64)
+}
+
+/// Pearson correlation of `a` and `b` over as many values as both have.
+pub fn correlation(a: &[f64], b: &[f64]) -> Option<f64> {
+    let n = cmp::min(a.len(), b.len());
+    if n < 2 {
+        return None;
+    }
+    let (a, b) = (&a[..n], &b[..n]);
+    let mean = |xs: &[f64]| xs.iter().sum::<f64>() / n as f64;
+    let (mean_a, mean_b) = (mean(a), mean(b));
+    let (mut covariance, mut variance_a, mut variance_b) = (0f64, 0f64, 0f64);
+    for (&x, &y) in a.iter().zip(b) {
+        covariance += (x - mean_a) * (y - mean_b);
+        variance_a += (x - mean_a) * (x - mean_a);
+        variance_b += (y - mean_b) * (y - mean_b);
+    }
+    if variance_a == 0f64 || variance_b == 0f64 {
+        return None;
+    }
+    // Rounding can take it just past 1 for series that are the same.
+    Some((covariance / (variance_a * variance_b).sqrt()).max(-1f64).min(1f64))
+}
+
+/// How alike `a` and `b` are, their entropy compared `block` bytes at a time.
+pub fn compare(a: &Source, b: &Source, block: usize) -> Result<Similarity, Error> {
+    let a_counts = try!(counts(try!(a.open()), block).map_err(|e| a.error(e)));
+    let b_counts = try!(counts(try!(b.open()), block).map_err(|e| b.error(e)));
+    Ok(Similarity {
+        names: (a.name(), b.name()),
+        lens: (a_counts.len, b_counts.len),
+        histogram: distance(&a_counts.histogram, &b_counts.histogram),
+        digraphs: distance(&a_counts.digraphs, &b_counts.digraphs),
+        entropy: correlation(&a_counts.entropy, &b_counts.entropy),
+        block: block,
+    })
+}
+
+/// `minrs similarity`: prints how alike `a` and `b` are as JSON, on one line.
+pub fn run(a: &str, b: &str, block: usize, raw: bool) -> Result<(), Error> {
+    let open = |path| if raw { Ok(Source::raw(path)) } else { Source::sniff(path) };
+    let similarity = try!(compare(&try!(open(a)), &try!(open(b)), block));
+    println!("{}", similarity.to_json());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use rustc_serialize::json::ToJson;
+
+    use entropy;
+    use fixtures;
+    use source::Source;
+    use super::{compare, correlation, counts, distance, Similarity};
+
+    fn compared(a: &PathBuf, b: &PathBuf) -> Similarity {
+        compare(&Source::raw(a.to_str().unwrap()), &Source::raw(b.to_str().unwrap()), 512).unwrap()
+    }
+
+    #[test]
+    fn files() {
+        let (text, patched, random) = (fixtures::path("text.bin"), fixtures::path("text-patched.bin"),
+                                       fixtures::path("random.bin"));
+        let same = compared(&text, &text);
+        assert_eq!((same.histogram, same.digraphs), (0f64, 0f64));
+        assert!((same.entropy.unwrap() - 1f64).abs() < 1e-9);
+        let close = compared(&text, &patched);
+        let far = compared(&text, &random);
+        assert!(0f64 < close.histogram && close.histogram < far.histogram, "{:?} {:?}", close, far);
+        assert!(0f64 < close.digraphs && close.digraphs < far.digraphs, "{:?} {:?}", close, far);
+        assert_eq!(far.lens, (2048, 12288));
+
+        let json = far.to_json();
+        assert_eq!(json.find("a").and_then(|a| a.as_string()), text.to_str());
+        assert_eq!(json.find("block").and_then(|block| block.as_u64()), Some(512));
+        assert!(json.find("entropy_correlation").is_some());
+    }
+
+    #[test]
+    fn one_pass() {
+        // The last block is shorter than the others.
+        let bytes = fixtures::read("text.bin");
+        let counts = counts(&bytes[..1500], 512).unwrap();
+        assert_eq!(counts.entropy, entropy::blocks(&bytes[..1500], 512).unwrap());
+        assert_eq!((counts.entropy.len(), counts.len), (3, 1500));
+    }
+
+    #[test]
+    fn measures() {
+        // Nothing in common, and nothing varies.
+        assert_eq!(distance(&[3, 0], &[0, 5]), 1f64);
+        assert_eq!(correlation(&[0.5, 0.5, 0.5], &[0.1, 0.9, 0.2]), None);
+        assert_eq!(correlation(&[0.1, 0.2, 0.3], &[0.6, 0.4, 0.2]).map(|r| r.round()), Some(-1f64));
+    }
+}
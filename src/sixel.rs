@@ -0,0 +1,116 @@
+//! Sixel encoding of `raster::Image`s, as understood by xterm, mlterm, foot, WezTerm…
+
+use std::collections::HashMap;
+
+use palette::Rgb;
+use raster::Image;
+
+/// Most terminals give images no more than this many colour registers.
+const REGISTERS: usize = 256;
+
+pub fn encode(image: &Image) -> String {
+    let (colours, indices) = quantize(image);
+
+    let mut out = String::new();
+    out.push_str("\x1bPq");
+    out.push_str(&format!("\"1;1;{};{}", image.width, image.height));
+    for (i, c) in colours.iter().enumerate() {
+        out.push_str(&format!("#{};2;{};{};{}", i,
+                              c[0] as u32 * 100 / 255,
+                              c[1] as u32 * 100 / 255,
+                              c[2] as u32 * 100 / 255));
+    }
+
+    // Each band is six rows of pixels, drawn once per colour it contains.
+    let width = image.width as usize;
+    for top in (0..image.height as usize).filter(|y| y % 6 == 0) {
+        let rows = ::std::cmp::min(6, image.height as usize - top);
+        let mut used = vec![false; colours.len()];
+        for y in top..top + rows {
+            for &i in &indices[y * width..(y + 1) * width] {
+                used[i] = true;
+            }
+        }
+        let mut first = true;
+        for colour in (0..colours.len()).filter(|&i| used[i]) {
+            if !first {
+                out.push('$');
+            }
+            first = false;
+            out.push_str(&format!("#{}", colour));
+            let mut run = (0u8, 0usize);
+            for x in 0..width {
+                let mut bits = 0u8;
+                for dy in 0..rows {
+                    if indices[(top + dy) * width + x] == colour {
+                        bits |= 1 << dy;
+                    }
+                }
+                if x > 0 && bits != run.0 {
+                    push_run(&mut out, run);
+                    run.1 = 0;
+                }
+                run = (bits, run.1 + 1);
+            }
+            push_run(&mut out, run);
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\");
+    out
+}
+
+fn push_run(out: &mut String, (bits, count): (u8, usize)) {
+    let c = (63 + bits) as char;
+    if count > 3 {
+        out.push_str(&format!("!{}{}", count, c));
+    } else {
+        for _ in 0..count {
+            out.push(c);
+        }
+    }
+}
+
+/// Palette of the image and the palette index of each pixel.
+/// Exact when there are few enough colours, otherwise reduced to 3-3-2 bits.
+fn quantize(image: &Image) -> (Vec<Rgb>, Vec<usize>) {
+    let mut colours = vec![];
+    let mut registers: HashMap<Rgb, usize> = HashMap::new();
+    for c in &image.pixels {
+        if !registers.contains_key(c) {
+            registers.insert(*c, colours.len());
+            colours.push(*c);
+        }
+    }
+    if colours.len() <= REGISTERS {
+        let indices = image.pixels.iter().map(|c| registers[c]).collect();
+        return (colours, indices);
+    }
+
+    let colours = (0..REGISTERS).map(|i| {
+        [((i >> 5) * 255 / 7) as u8, (((i >> 2) & 7) * 255 / 7) as u8, ((i & 3) * 255 / 3) as u8]
+    }).collect();
+    let indices = image.pixels.iter().map(|c| {
+        ((c[0] as usize >> 5) << 5) | ((c[1] as usize >> 5) << 2) | (c[2] as usize >> 6)
+    }).collect();
+    (colours, indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use raster::Image;
+    use super::encode;
+
+    #[test]
+    fn two_by_two() {
+        let (red, blue) = ([255, 0, 0], [0, 0, 255]);
+        let image = Image{width: 2, height: 2, pixels: vec![red, blue, blue, red]};
+        assert_eq!(encode(&image), "\x1bPq\"1;1;2;2#0;2;100;0;0#1;2;0;0;100#0@A$#1A@-\x1b\\");
+    }
+
+    #[test]
+    fn runs() {
+        let image = Image{width: 5, height: 1, pixels: vec![[0, 0, 0]; 5]};
+        assert_eq!(encode(&image), "\x1bPq\"1;1;5;1#0;2;0;0;0#0!5@-\x1b\\");
+    }
+}
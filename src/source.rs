@@ -0,0 +1,262 @@
+//! Where the bytes of an input come from: the file itself, or what it decompresses to
+//! when it starts with the magic bytes of gzip, xz, zstd or bzip2, or one member of
+//! the archive that either of those is, or the memory of a process. A decompressed
+//! stream can't seek, so reading from an offset decompresses all that comes before it
+//! again, a buffer at a time, to keep memory bounded whatever the size of the input.
+
+use std::cmp;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+
+use bzip2::read::BzDecoder;
+use flate2::read::{DeflateDecoder, MultiGzDecoder};
+use xz2::read::XzDecoder;
+use zstd;
+
+use archive::Member;
+use error::Error;
+use process::Process;
+use sparse;
+use super::{file_len, read_bytes};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Codec {
+    Gzip,
+    Xz,
+    Zstd,
+    Bzip2,
+}
+
+const MAGIC: [(Codec, &'static [u8]); 3] = [
+    (Codec::Gzip, b"\x1f\x8b"),
+    (Codec::Xz, b"\xfd7zXZ\x00"),
+    (Codec::Zstd, b"\x28\xb5\x2f\xfd"),
+];
+
+/// What follows "BZh" and the block size, from 1 to 9: the magic of the first block,
+/// or of the end of an empty stream. "BZh" alone starts too much text.
+const BZIP2_MAGIC: [&'static [u8]; 2] = [b"1AY&SY", b"\x17rE8P\x90"];
+
+impl Codec {
+    /// The codec whose magic bytes `bytes` start with, if any.
+    pub fn sniff(bytes: &[u8]) -> Option<Codec> {
+        if bytes.len() >= 10 && bytes.starts_with(b"BZh") && b'1' <= bytes[3] && bytes[3] <= b'9' &&
+            BZIP2_MAGIC.contains(&&bytes[4..10]) {
+            return Some(Codec::Bzip2);
+        }
+        MAGIC.iter().find(|&&(_, magic)| bytes.starts_with(magic)).map(|&(codec, _)| codec)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Codec::Gzip => "gzip",
+            Codec::Xz => "xz",
+            Codec::Zstd => "zstd",
+            Codec::Bzip2 => "bzip2",
+        }
+    }
+}
+
+/// One input, as shown.
+#[derive(Debug, Clone)]
+pub struct Source<'a> {
+    pub path: &'a str,
+    /// What the file is decompressed with, if it is.
+    pub codec: Option<Codec>,
+    /// The one member shown of the archive that the file, once decompressed, is.
+    pub member: Option<Member>,
+    /// The process whose memory the file at `path` is, read through its mappings.
+    pub process: Option<&'a Process>,
+    /// Where the file has holes, and how many bytes each is, as `sparse::holes` finds them.
+    pub holes: Vec<(u64, u64)>,
+}
+
+impl<'a> Source<'a> {
+    /// The bytes of the file at `path` as they are.
+    pub fn raw(path: &'a str) -> Source<'a> {
+        Source{path: path, codec: None, member: None, process: None, holes: vec![]}
+    }
+
+    /// What the file at `path` decompresses to when it is compressed, else its bytes.
+    pub fn sniff(path: &'a str) -> Result<Source<'a>, Error> {
+        let head = try!(read_bytes(path, 0, 10));
+        Ok(Source{path: path, codec: Codec::sniff(&head), member: None, process: None, holes: vec![]})
+    }
+
+    /// The memory of `process`.
+    pub fn process(process: &'a Process) -> Source<'a> {
+        Source{path: &process.path, codec: None, member: None, process: Some(process),
+                holes: vec![]}
+    }
+
+    /// `member` of the archive that this source is.
+    pub fn member(&self, member: Member) -> Source<'a> {
+        Source {
+            path: self.path,
+            codec: self.codec,
+            member: Some(member),
+            process: self.process,
+            holes: self.holes.clone(),
+        }
+    }
+
+    /// How the member is called, after the path of the archive.
+    pub fn name(&self) -> String {
+        match self.member {
+            Some(ref member) => format!("{}:{}", self.path, member.name),
+            None => self.path.to_owned(),
+        }
+    }
+
+    /// Where files made from this input go, the bookmarks of a member beside the archive.
+    /// Those of a process go in the working directory, `/proc` being read only.
+    pub fn file_name(&self) -> String {
+        match (&self.member, self.process) {
+            (&Some(ref member), _) => format!("{}.{}", self.path, member.name.replace('/', "_")),
+            (&None, Some(process)) => format!("pid{}", process.pid),
+            (&None, None) => self.path.to_owned(),
+        }
+    }
+
+    /// What the sidecar of the bookmarks of this input is called, before `.minrs.json`.
+    /// Those of the bytes a file decompresses to are kept apart from those of the file
+    /// as it is, their offsets being into different bytes.
+    pub fn project(&self) -> String {
+        match (&self.member, self.process, self.codec) {
+            (&None, None, Some(codec)) => format!("{}.{}", self.path, codec.name()),
+            _ => self.file_name(),
+        }
+    }
+
+    /// Where the bytes shown are in no mapping of a process or in a hole of the file,
+    /// and how many there are.
+    pub fn gaps(&self) -> Vec<(u64, u64)> {
+        match (&self.member, self.process, self.codec) {
+            (&None, Some(process), _) => process.gaps(),
+            (&None, None, None) => self.holes.clone(),
+            _ => vec![],
+        }
+    }
+
+    /// All of the bytes, from the first one.
+    pub fn open(&self) -> Result<Box<Read>, Error> {
+        match self.member {
+            Some(ref member) => {
+                let stored = try!(self.container_at(member.offset)).take(member.len);
+                Ok(if member.deflated { Box::new(DeflateDecoder::new(stored)) } else { Box::new(stored) })
+            }
+            None => self.container(),
+        }
+    }
+
+    /// The bytes of the file, decompressed if need be, from the first one.
+    fn container(&self) -> Result<Box<Read>, Error> {
+        if let Some(process) = self.process {
+            return Ok(Box::new(try!(process.open_at(0).map_err(|e| self.error(e)))));
+        }
+        let f = BufReader::new(try!(File::open(self.path).map_err(|e| self.error(e))));
+        Ok(match self.codec {
+            None if !self.holes.is_empty() => return self.container_at(0),
+            None => Box::new(f),
+            Some(Codec::Gzip) => Box::new(MultiGzDecoder::new(f)),
+            Some(Codec::Xz) => Box::new(XzDecoder::new_multi_decoder(f)),
+            Some(Codec::Zstd) => Box::new(try!(zstd::Decoder::new(f).map_err(|e| self.error(e)))),
+            Some(Codec::Bzip2) => Box::new(BzDecoder::new(f)),
+        })
+    }
+
+    /// The bytes of the file, decompressed if need be, from `offset` on.
+    fn container_at(&self, offset: u64) -> Result<Box<Read>, Error> {
+        if let Some(process) = self.process {
+            return Ok(Box::new(try!(process.open_at(offset).map_err(|e| self.error(e)))));
+        }
+        if self.codec.is_none() && !self.holes.is_empty() {
+            return Ok(Box::new(try!(sparse::open_at(self.path, &self.holes, offset).map_err(|e| self.error(e)))));
+        }
+        if self.codec.is_none() {
+            let mut f = try!(File::open(self.path).map_err(|e| self.error(e)));
+            try!(f.seek(SeekFrom::Start(offset)).map_err(|e| self.error(e)));
+            return Ok(Box::new(BufReader::new(f)));
+        }
+        let mut reader = try!(self.container());
+        try!(io::copy(&mut reader.by_ref().take(offset), &mut io::sink()).map_err(|e| self.error(e)));
+        Ok(reader)
+    }
+
+    /// The bytes from `offset` on.
+    pub fn open_at(&self, offset: u64) -> Result<Box<Read>, Error> {
+        match self.member {
+            Some(ref member) if !member.deflated => {
+                let start = member.offset + cmp::min(offset, member.len);
+                Ok(Box::new(try!(self.container_at(start)).take(member.len.saturating_sub(offset))))
+            }
+            Some(_) => {
+                let mut reader = try!(self.open());
+                try!(io::copy(&mut reader.by_ref().take(offset), &mut io::sink()).map_err(|e| self.error(e)));
+                Ok(reader)
+            }
+            None => self.container_at(offset),
+        }
+    }
+
+    /// How many bytes there are, found by decompressing them all if need be.
+    pub fn len(&self) -> Result<u64, Error> {
+        match (&self.member, self.process, self.codec) {
+            (&Some(ref member), _, _) => Ok(member.size),
+            (&None, Some(process), _) => Ok(process.len()),
+            (&None, None, None) => file_len(self.path),
+            (&None, None, Some(_)) => {
+                let mut reader = try!(self.open());
+                io::copy(&mut reader, &mut io::sink()).map_err(|e| self.error(e))
+            }
+        }
+    }
+
+    /// At most `len` bytes from `offset`.
+    pub fn read(&self, offset: u64, len: u64) -> Result<Vec<u8>, Error> {
+        if let (&None, Some(process)) = (&self.member, self.process) {
+            return process.read(offset, len).map_err(|e| self.error(e));
+        }
+        if self.codec.is_none() && self.member.is_none() {
+            return sparse::read(self.path, &self.holes, offset, len);
+        }
+        let mut buffer = vec![];
+        try!(try!(self.open_at(offset)).take(len).read_to_end(&mut buffer).map_err(|e| self.error(e)));
+        Ok(buffer)
+    }
+
+    pub fn error(&self, e: io::Error) -> Error {
+        Error::Input(self.name(), e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fixtures;
+    use super::{Codec, Source};
+
+    #[test]
+    fn decompress() {
+        let bytes = fixtures::read("strings.bin");
+        assert_eq!(Codec::sniff(&bytes), None);
+        for (extension, codec, data) in fixtures::compressed(&bytes) {
+            let path = fixtures::write(&format!("source.bin.{}", extension), &data);
+            let source = Source::sniff(path.to_str().unwrap()).unwrap();
+            assert_eq!(source.codec, Some(codec));
+            assert_eq!(source.len().unwrap(), bytes.len() as u64);
+            assert_eq!(source.read(300, 50).unwrap(), &bytes[300..350]);
+            assert_eq!(Source::raw(source.path).read(0, 1 << 20).unwrap(), data);
+            // Bookmarks of what it decompresses to are kept apart from those of the file.
+            assert_eq!(source.project(), format!("{}.{}", source.path, codec.name()));
+            assert_eq!(Source::raw(source.path).project(), source.path);
+        }
+    }
+
+    #[test]
+    fn bzh_text() {
+        let path = fixtures::write("source-bzh.txt", b"BZh, the start of a text file");
+        let source = Source::sniff(path.to_str().unwrap()).unwrap();
+        assert_eq!(source.codec, None);
+        assert_eq!(source.read(0, 3).unwrap(), b"BZh");
+    }
+}
@@ -0,0 +1,161 @@
+//! Holes in sparse files, such as disk images, where no block was ever written: the
+//! filesystem knows where they are, through `SEEK_DATA` and `SEEK_HOLE`, so their
+//! zeros are never read, and they are drawn apart from bytes that are zero. Systems
+//! without those read every file as if it had no holes.
+
+use std::cmp;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "freebsd"))]
+use std::os::unix::io::AsRawFd;
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "freebsd"))]
+use libc;
+
+use error::Error;
+use super::read_bytes;
+
+/// Where `file` seeks to from `offset` with `whence`, `None` past the last data.
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "freebsd"))]
+fn seek(file: &File, offset: u64, whence: libc::c_int) -> io::Result<Option<u64>> {
+    match unsafe { libc::lseek(file.as_raw_fd(), offset as libc::off_t, whence) } {
+        -1 => match io::Error::last_os_error() {
+            ref e if e.raw_os_error() == Some(libc::ENXIO) => Ok(None),
+            e => Err(e),
+        },
+        at => Ok(Some(at as u64)),
+    }
+}
+
+/// Where the holes of the `len` bytes of the file at `path` start, and how many bytes
+/// each is. None where the filesystem doesn't keep track of them.
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "freebsd"))]
+pub fn holes(path: &str, len: u64) -> Result<Vec<(u64, u64)>, Error> {
+    let error = |e: io::Error| Error::Input(path.to_owned(), e);
+    let file = try!(File::open(path).map_err(&error));
+    let mut holes = vec![];
+    let mut offset = 0;
+    while offset < len {
+        let data = match seek(&file, offset, libc::SEEK_DATA) {
+            Ok(data) => cmp::min(data.unwrap_or(len), len),
+            Err(ref e) if e.raw_os_error() == Some(libc::EINVAL) => return Ok(vec![]),
+            Err(e) => return Err(error(e)),
+        };
+        if data > offset {
+            holes.push((offset, data - offset));
+        }
+        if data == len {
+            break;
+        }
+        offset = try!(seek(&file, data, libc::SEEK_HOLE).map_err(&error)).unwrap_or(len);
+    }
+    Ok(holes)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "freebsd")))]
+pub fn holes(_: &str, _: u64) -> Result<Vec<(u64, u64)>, Error> {
+    Ok(vec![])
+}
+
+/// At most `len` bytes of the file at `path` from `offset`, `holes` read as zeros
+/// without reading them.
+pub fn read(path: &str, holes: &[(u64, u64)], offset: u64, len: u64) -> Result<Vec<u8>, Error> {
+    let end = offset.saturating_add(len);
+    let mut buffer = vec![];
+    let mut at = offset;
+    for &(start, hole_len) in holes.iter().filter(|&&(start, hole_len)| start + hole_len > offset) {
+        if start >= end {
+            break;
+        }
+        if start > at {
+            let data = try!(read_bytes(path, at, start - at));
+            if (data.len() as u64) < start - at {
+                buffer.extend(data);
+                return Ok(buffer);
+            }
+            buffer.extend(data);
+            at = start;
+        }
+        let zeros = cmp::min(start + hole_len, end) - at;
+        buffer.resize(buffer.len() + zeros as usize, 0);
+        at += zeros;
+    }
+    if at < end {
+        buffer.extend(try!(read_bytes(path, at, end - at)));
+    }
+    Ok(buffer)
+}
+
+/// The bytes of the file at `path` from `offset` on, `holes` read as zeros without
+/// reading them.
+pub fn open_at(path: &str, holes: &[(u64, u64)], offset: u64) -> io::Result<Reader> {
+    Ok(Reader{file: try!(File::open(path)), holes: holes.to_vec(), offset: offset, seeked: false})
+}
+
+pub struct Reader {
+    file: File,
+    holes: Vec<(u64, u64)>,
+    offset: u64,
+    /// Whether `file` is at `offset`, as it is but after a hole.
+    seeked: bool,
+}
+
+impl Read for Reader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let offset = self.offset;
+        let next = self.holes.iter().cloned().find(|&(start, len)| start + len > offset);
+        let len = match next {
+            Some((start, len)) if start <= offset => {
+                let zeros = cmp::min(start + len - offset, buf.len() as u64) as usize;
+                for b in &mut buf[..zeros] {
+                    *b = 0;
+                }
+                self.seeked = false;
+                zeros
+            }
+            _ => {
+                if !self.seeked {
+                    try!(self.file.seek(SeekFrom::Start(offset)));
+                    self.seeked = true;
+                }
+                let until = next.map_or(buf.len() as u64, |(start, _)| start - offset);
+                let len = cmp::min(until, buf.len() as u64) as usize;
+                try!(self.file.read(&mut buf[..len]))
+            }
+        };
+        self.offset += len as u64;
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use fixtures;
+    use super::{open_at, read};
+
+    const HOLES: [(u64, u64); 2] = [(4096, (1 << 20) - 4096), ((1 << 20) + 4096, (1 << 20) - 4096)];
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn holes() {
+        let (path, _) = fixtures::sparse("sparse-holes.img");
+        assert_eq!(super::holes(path.to_str().unwrap(), 2 << 20).unwrap(), HOLES.to_vec());
+    }
+
+    #[test]
+    fn reads() {
+        let (path, expected) = fixtures::sparse("sparse-reads.img");
+        let path = path.to_str().unwrap();
+        assert_eq!(read(path, &HOLES, 0, 3 << 20).unwrap(), expected);
+        assert_eq!(read(path, &HOLES, 4000, 200).unwrap(), &expected[4000..4200]);
+        let across = (1 << 20) - 100;
+        assert_eq!(read(path, &HOLES, across, 200).unwrap(), &expected[across as usize..across as usize + 200]);
+        let mut bytes = vec![];
+        open_at(path, &HOLES, 1000).unwrap().read_to_end(&mut bytes).unwrap();
+        assert_eq!(bytes, &expected[1000..]);
+        // Without holes, the same bytes are read from the file.
+        assert_eq!(read(path, &[], across, 200).unwrap(), &expected[across as usize..across as usize + 200]);
+    }
+}
@@ -0,0 +1,60 @@
+//! Finding the length of the records that repeat through the data, so that laying it
+//! out that many bytes to a row lines fields up in columns.
+
+/// Bytes looked at from where detection starts.
+pub const SAMPLE: u64 = 16 * 1024;
+
+/// How many times as often as at the average lag bytes must repeat at a stride.
+const SALIENCE: f64 = 2.0;
+
+/// Lags scoring within this share of the best one count as just as good, so that
+/// the stride itself wins over its multiples.
+const TOLERANCE: f64 = 0.95;
+
+/// The lag from 2 to `max` bytes at which `bytes` most often repeat themselves,
+/// if any stands out from the others.
+pub fn detect(bytes: &[u8], max: usize) -> Option<usize> {
+    let max = if bytes.len() > 1 { ::std::cmp::min(max, bytes.len() / 2) } else { 0 };
+    if max < 2 {
+        return None;
+    }
+    let scores: Vec<f64> = (2..max + 1).map(|lag| {
+        let same = bytes.iter().zip(&bytes[lag..]).filter(|&(a, b)| a == b).count();
+        same as f64 / (bytes.len() - lag) as f64
+    }).collect();
+    let best = scores.iter().cloned().fold(0f64, f64::max);
+    let mean = scores.iter().sum::<f64>() / scores.len() as f64;
+    if best == 0f64 || best < SALIENCE * mean {
+        return None;
+    }
+    scores.iter().position(|&score| score >= TOLERANCE * best).map(|i| i + 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use fixtures;
+    use super::detect;
+
+    #[test]
+    fn records() {
+        let random = fixtures::read("random.bin");
+        // Records of 37 bytes: a tag, a little-endian counter and random bytes.
+        let bytes: Vec<u8> = random.chunks(32).take(64).enumerate()
+            .flat_map(|(i, chunk)| {
+                let mut record = b"REC".to_vec();
+                record.extend_from_slice(&[i as u8, (i >> 8) as u8]);
+                record.extend_from_slice(chunk);
+                record
+            })
+            .collect();
+        assert_eq!(detect(&bytes, 128), Some(37));
+        assert_eq!(detect(&bytes, 36), None);
+    }
+
+    #[test]
+    fn nothing_repeats() {
+        assert_eq!(detect(&fixtures::read("random.bin"), 128), None);
+        assert_eq!(detect(&[0; 1024], 128), None);
+        assert_eq!(detect(&[7], 128), None);
+    }
+}
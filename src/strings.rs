@@ -0,0 +1,138 @@
+//! Printable strings as `strings(1)` finds them, in ASCII and in UTF-16LE,
+//! highlighted over the bytes and listed beside them.
+
+use std::io::{self, Read};
+
+use font;
+use palette::Rgb;
+use raster::Image;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Encoding {
+    Ascii,
+    Utf16le,
+}
+
+#[derive(Debug, Clone)]
+pub struct Run {
+    pub offset: u64,
+    /// Length in bytes, twice the number of characters in UTF-16.
+    pub len: u64,
+    pub encoding: Encoding,
+    pub text: String,
+}
+
+/// Characters found so far from `offset`, not yet known to be long enough.
+struct Pending {
+    offset: u64,
+    text: String,
+}
+
+/// Printable ASCII and tab, as `strings(1)` takes them.
+fn printable(b: u8) -> bool {
+    b == b'\t' || (0x20 <= b && b <= 0x7e)
+}
+
+/// Every run of at least `min_len` printable characters, by offset.
+pub fn scan<R: Read>(mut reader: R, min_len: usize) -> io::Result<Vec<Run>> {
+    let mut runs = vec![];
+    let mut ascii: Option<Pending> = None;
+    // UTF-16 characters may start at even or at odd offsets: one run in the making for each.
+    let mut wide: [Option<Pending>; 2] = [None, None];
+    let mut previous: Option<u8> = None;
+    let mut offset = 0u64;
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = try!(reader.read(&mut buffer));
+        if read == 0 {
+            break;
+        }
+        for &b in &buffer[..read] {
+            if printable(b) {
+                if ascii.is_none() {
+                    ascii = Some(Pending{offset: offset, text: String::new()});
+                }
+                ascii.as_mut().unwrap().text.push(b as char);
+            } else if let Some(pending) = ascii.take() {
+                finish(&mut runs, pending, Encoding::Ascii, min_len);
+            }
+
+            // The pair of bytes ending here is a character when it is printable ASCII then zero.
+            if let Some(p) = previous {
+                let slot = &mut wide[((offset - 1) % 2) as usize];
+                if printable(p) && b == 0 {
+                    if slot.is_none() {
+                        *slot = Some(Pending{offset: offset - 1, text: String::new()});
+                    }
+                    slot.as_mut().unwrap().text.push(p as char);
+                } else if let Some(pending) = slot.take() {
+                    finish(&mut runs, pending, Encoding::Utf16le, min_len);
+                }
+            }
+            previous = Some(b);
+            offset += 1;
+        }
+    }
+    if let Some(pending) = ascii.take() {
+        finish(&mut runs, pending, Encoding::Ascii, min_len);
+    }
+    for slot in wide.iter_mut() {
+        if let Some(pending) = slot.take() {
+            finish(&mut runs, pending, Encoding::Utf16le, min_len);
+        }
+    }
+    runs.sort_by(|a, b| a.offset.cmp(&b.offset));
+    Ok(runs)
+}
+
+fn finish(runs: &mut Vec<Run>, pending: Pending, encoding: Encoding, min_len: usize) {
+    if pending.text.len() < min_len {
+        return;
+    }
+    let chars = pending.text.len() as u64;
+    runs.push(Run {
+        offset: pending.offset,
+        len: if encoding == Encoding::Utf16le { 2 * chars } else { chars },
+        encoding: encoding,
+        text: pending.text,
+    });
+}
+
+const BACKGROUND: Rgb = [24, 24, 24];
+const ASCII: Rgb = [224, 224, 224];
+const UTF16: Rgb = [128, 208, 224];
+
+/// One line per run from `first`, as many as fit in `width`x`height` pixels,
+/// `selected` drawn inverted. UTF-16 strings are tinted blue.
+pub fn list(runs: &[Run], first: usize, selected: Option<usize>, width: u32, height: u32) -> Image {
+    let mut image = Image::new(width, height);
+    for pixel in image.pixels.iter_mut() {
+        *pixel = BACKGROUND;
+    }
+    let lines = (height / font::HEIGHT) as usize;
+    for (line, (i, run)) in runs.iter().enumerate().skip(first).take(lines).enumerate() {
+        let top = line as u32 * font::HEIGHT;
+        let mut ink = if run.encoding == Encoding::Utf16le { UTF16 } else { ASCII };
+        if selected == Some(i) {
+            image.fill(0, top, width, font::HEIGHT, ink);
+            ink = BACKGROUND;
+        }
+        image.text(1, top + 1, &format!("{:08x} {}", run.offset, run.text.replace('\t', " ")), ink);
+    }
+    image
+}
+
+#[cfg(test)]
+mod tests {
+    use fixtures;
+    use super::scan;
+
+    #[test]
+    fn runs() {
+        let found = scan(&fixtures::read("strings.bin")[..], 4).unwrap();
+        let texts: Vec<(u64, &str)> = found.iter().map(|run| (run.offset, run.text.as_str())).collect();
+        assert_eq!(texts, vec![(37, "GetProcAddress"), (102, "C:\\Windows\\System32"),
+                               (210, "/usr/lib/libc.so.6"), (241, "odd aligned"),
+                               (323, "Copyright (C) 2017\tminrs")]);
+    }
+}
@@ -0,0 +1,114 @@
+//! Drawing in the terminal, for when there is no display to open a window on (e.g. over SSH):
+//! with Unicode half blocks and 24-bit ANSI colours anywhere,
+//! or as actual images where Sixel or the kitty graphics protocol are supported.
+
+use std;
+use std::io::Write;
+
+use libc;
+use rustc_serialize::base64::{self, ToBase64};
+use term_size;
+
+use error::Error;
+use raster::Image;
+use sixel;
+
+#[derive(Debug, Clone, Copy, PartialEq, RustcDecodable)]
+pub enum Graphics {
+    Blocks,
+    Sixel,
+    Kitty,
+}
+
+/// Cell size assumed when the terminal does not report its size in pixels.
+const CELL: (u32, u32) = (8, 16);
+
+/// Pixels that fit in the terminal, keeping the last line for the prompt.
+/// Half blocks draw two rows per line, one column per character.
+pub fn size(graphics: Graphics) -> (u32, u32) {
+    let (columns, lines) = term_size::dimensions().unwrap_or((80, 24));
+    let (columns, lines) = (columns as u32, lines as u32);
+    let rows = std::cmp::max(1, lines.saturating_sub(1));
+    match graphics {
+        Graphics::Blocks => (columns, 2 * rows),
+        Graphics::Sixel | Graphics::Kitty => {
+            let (width, height) = pixels().unwrap_or((columns * CELL.0, lines * CELL.1));
+            (width, height / lines * rows)
+        }
+    }
+}
+
+/// Size of the terminal in pixels, if it says.
+#[cfg(unix)]
+fn pixels() -> Option<(u32, u32)> {
+    let mut ws: libc::winsize = unsafe { std::mem::zeroed() };
+    let ok = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut ws) } == 0;
+    if ok && ws.ws_xpixel > 0 && ws.ws_ypixel > 0 {
+        Some((ws.ws_xpixel as u32, ws.ws_ypixel as u32))
+    } else {
+        None
+    }
+}
+
+#[cfg(not(unix))]
+fn pixels() -> Option<(u32, u32)> {
+    None
+}
+
+pub fn show(image: &Image, graphics: Graphics) -> Result<(), Error> {
+    if image.width == 0 || image.height == 0 {
+        return Err(Error::Layout(image.width, image.height));
+    }
+    match graphics {
+        Graphics::Blocks => print(image),
+        Graphics::Sixel => print_sixel(image),
+        Graphics::Kitty => print_kitty(image),
+    }.map_err(|e| Error::Output("stdout".to_owned(), e))
+}
+
+/// Clears the screen then draws two rows of pixels per line:
+/// an upper half block in the colour of the top pixel over the colour of the bottom one.
+pub fn print(image: &Image) -> std::io::Result<()> {
+    let stdout = std::io::stdout();
+    let mut out = std::io::BufWriter::new(stdout.lock());
+    try!(write!(out, "\x1b[2J\x1b[H"));
+    for line in 0..(image.height + 1) / 2 {
+        let y = 2 * line;
+        for x in 0..image.width {
+            let top = image.get(x, y);
+            let bottom = if y + 1 < image.height { image.get(x, y + 1) } else { [0, 0, 0] };
+            try!(write!(out, "\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m\u{2580}",
+                        top[0], top[1], top[2], bottom[0], bottom[1], bottom[2]));
+        }
+        try!(writeln!(out, "\x1b[0m"));
+    }
+    out.flush()
+}
+
+pub fn print_sixel(image: &Image) -> std::io::Result<()> {
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    try!(writeln!(out, "{}", sixel::encode(image)));
+    out.flush()
+}
+
+/// Sends the pixels as 24-bit RGB in base64 chunks, the largest the protocol allows.
+pub fn print_kitty(image: &Image) -> std::io::Result<()> {
+    let data = image.rgb().to_base64(base64::STANDARD);
+    let chunks: Vec<&[u8]> = data.as_bytes().chunks(4096).collect();
+
+    let stdout = std::io::stdout();
+    let mut out = std::io::BufWriter::new(stdout.lock());
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = if i + 1 < chunks.len() { 1 } else { 0 };
+        if i == 0 {
+            try!(write!(out, "\x1b_Ga=T,f=24,s={},v={},m={};", image.width, image.height, more));
+        } else {
+            try!(write!(out, "\x1b_Gm={};", more));
+        }
+        try!(out.write_all(chunk));
+        try!(write!(out, "\x1b\\"));
+    }
+    try!(writeln!(out, ""));
+    out.flush()
+}
@@ -0,0 +1,202 @@
+//! `minrs thumbs <dir> --out=<out>`: a picture of every file under a directory,
+//! made on all cores, and an `index.html` to look at them all at once.
+
+use std::fs::{self, File};
+use std::io::{self, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+use num_cpus;
+
+use error::Error;
+use palette::Mode;
+use raster;
+
+struct Thumb {
+    /// Path of the file, relative to the walked directory.
+    name: PathBuf,
+    len: u64,
+}
+
+pub fn run(dir_name: &str, out_name: &str, size: u32, mode: Mode) -> Result<(), Error> {
+    if size == 0 {
+        return Err(Error::Layout(size, size));
+    }
+    let dir = Path::new(dir_name);
+    let out = Path::new(out_name);
+    try!(fs::create_dir_all(out).map_err(|e| Error::Output(out_name.to_owned(), e)));
+    // Pictures from a run before aren't files to make pictures of.
+    let skip = try!(fs::canonicalize(out).map_err(|e| Error::Output(out_name.to_owned(), e)));
+    let mut files = vec![];
+    try!(walk(dir, &skip, &mut files).map_err(|e| Error::Input(dir_name.to_owned(), e)));
+    files.sort();
+    debug!("{:?} files under {:?}", files.len(), dir);
+
+    let queue = Arc::new(Mutex::new(files.into_iter()));
+    let (tx, rx) = mpsc::channel();
+    let workers: Vec<_> = (0..num_cpus::get()).map(|_| {
+        let queue = queue.clone();
+        let tx = tx.clone();
+        let dir = dir.to_path_buf();
+        let out = out.to_path_buf();
+        thread::spawn(move || loop {
+            let path = match queue.lock().unwrap().next() {
+                Some(path) => path,
+                None => return,
+            };
+            let result = thumb(&dir, &path, &out, size, mode);
+            tx.send((path, result)).unwrap();
+        })
+    }).collect();
+    drop(tx);
+
+    let mut thumbs = vec![];
+    for (path, result) in rx {
+        match result {
+            Ok(thumb) => thumbs.push(thumb),
+            Err(e) => { let _ = writeln!(io::stderr(), "skipping {:?}: {}", path, e); }
+        }
+    }
+    for worker in workers {
+        worker.join().unwrap();
+    }
+    thumbs.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let index_html = out.join("index.html");
+    try!(index(dir, &index_html, size, &thumbs)
+         .map_err(|e| Error::Output(index_html.to_string_lossy().into_owned(), e)));
+    debug!("wrote {:?} thumbnails and {:?}", thumbs.len(), index_html);
+    Ok(())
+}
+
+/// Regular files under `dir` but those under `skip`, a canonical path, not following
+/// symbolic links to directories.
+fn walk(dir: &Path, skip: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in try!(fs::read_dir(dir)) {
+        let path = try!(entry).path();
+        let meta = try!(fs::symlink_metadata(&path));
+        if meta.is_dir() {
+            if try!(fs::canonicalize(&path)) != skip {
+                try!(walk(&path, skip, files));
+            }
+        } else if meta.is_file() {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Renders the whole of `path` as `out/<relative path>.png`.
+fn thumb(dir: &Path, path: &Path, out: &Path, size: u32, mode: Mode) -> io::Result<Thumb> {
+    let name = path.strip_prefix(dir).unwrap().to_path_buf();
+    let png = out.join(png_name(&name));
+    try!(fs::create_dir_all(png.parent().unwrap()));
+
+    let f = try!(File::open(path));
+    let len = try!(f.metadata()).len();
+    let image = try!(raster::overview(mode, size, size, BufReader::new(f), len));
+    try!(image.save_png(&png));
+    Ok(Thumb{name: name, len: len})
+}
+
+fn png_name(name: &Path) -> PathBuf {
+    let mut png = name.as_os_str().to_owned();
+    png.push(".png");
+    PathBuf::from(png)
+}
+
+fn index(dir: &Path, path: &Path, size: u32, thumbs: &[Thumb]) -> io::Result<()> {
+    let mut html = io::BufWriter::new(try!(File::create(path)));
+    try!(writeln!(html, "<!DOCTYPE html>\n<meta charset=\"utf-8\">\n<title>{}</title>",
+                  escape(&dir.to_string_lossy())));
+    try!(writeln!(html, "<style>\n\
+                         body {{ background: #111; color: #ccc; font: 11px monospace; }}\n\
+                         figure {{ display: inline-block; vertical-align: top; margin: 4px; width: {}px; }}\n\
+                         figcaption {{ word-wrap: break-word; }}\n\
+                         img {{ image-rendering: pixelated; }}\n\
+                         </style>", size));
+    for thumb in thumbs {
+        let src = url(&png_name(&thumb.name));
+        let name = escape(&thumb.name.to_string_lossy());
+        try!(writeln!(html, "<figure><a href=\"{0}\"><img src=\"{0}\" width=\"{1}\" height=\"{1}\" alt=\"{2}\"></a>\
+                             <figcaption>{2}<br>{3} bytes</figcaption></figure>",
+                      src, size, name, thumb.len));
+    }
+    Ok(())
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Percent-encodes a relative path for use in an `href`.
+fn url(path: &Path) -> String {
+    let mut url = String::new();
+    for (i, component) in path.iter().enumerate() {
+        if i > 0 {
+            url.push('/');
+        }
+        for &b in component.to_string_lossy().as_bytes() {
+            let c = b as char;
+            if (b < 0x80 && c.is_alphanumeric()) || "-_.~".contains(c) {
+                url.push(c);
+            } else {
+                url.push_str(&format!("%{:02X}", b));
+            }
+        }
+    }
+    url
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{self, File};
+    use std::io::Read;
+    use std::path::{Path, PathBuf};
+
+    use fixtures;
+    use palette::Mode;
+    use super::{escape, index, png_name, run, url, Thumb};
+
+    #[test]
+    fn urls() {
+        assert_eq!(url(Path::new("a b/\u{fc}.bin.png")), "a%20b/%C3%BC.bin.png");
+        assert_eq!(url(Path::new("dir/x_y-z~1.png")), "dir/x_y-z~1.png");
+        assert_eq!(url(Path::new("100%#?.png")), "100%25%23%3F.png");
+        assert_eq!(escape("<a href=\"x\">&</a>"), "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;");
+    }
+
+    #[test]
+    fn out_inside() {
+        let dir = fixtures::out("thumbs");
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fixtures::write("thumbs/sub/text.bin", &fixtures::read("text.bin"));
+        let out = dir.join("pictures");
+        let (dir, out) = (dir.to_str().unwrap(), out.to_str().unwrap());
+        // Run twice, the second time with the pictures of the first under the directory.
+        for _ in 0..2 {
+            run(dir, out, 16, Mode::Four).unwrap();
+        }
+        let mut html = String::new();
+        File::open(Path::new(out).join("index.html")).unwrap().read_to_string(&mut html).unwrap();
+        assert_eq!(html.matches("<figure>").count(), 1);
+        assert!(Path::new(out).join("sub/text.bin.png").is_file());
+        assert!(!Path::new(out).join("pictures").exists());
+    }
+
+    #[test]
+    fn index_html() {
+        let path = fixtures::out("index.html");
+        let thumbs = vec![Thumb{name: PathBuf::from("a&b/c d.bin"), len: 3}];
+        index(Path::new("<dir>"), &path, 32, &thumbs).unwrap();
+        let mut html = String::new();
+        File::open(&path).unwrap().read_to_string(&mut html).unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>\n<meta charset=\"utf-8\">\n<title>&lt;dir&gt;</title>\n"));
+        assert!(html.contains("width: 32px;"));
+        assert!(html.ends_with("<figure><a href=\"a%26b/c%20d.bin.png\"><img src=\"a%26b/c%20d.bin.png\" \
+                                width=\"32\" height=\"32\" alt=\"a&amp;b/c d.bin\"></a>\
+                                <figcaption>a&amp;b/c d.bin<br>3 bytes</figcaption></figure>\n"));
+        assert_eq!(png_name(Path::new("a/b.bin")), PathBuf::from("a/b.bin.png"));
+    }
+}
@@ -8,5 +8,6 @@ out vec2 pos;
 void main() {
     gl_PointSize = 1;
     gl_Position = vec4(position, 0, 1);
-    pos = position;
+    // Texture coordinates: first byte at the top left of the window.
+    pos = vec2(position.x + 1, 1 - position.y) / 2;
 }